@@ -0,0 +1,135 @@
+use std::fmt::Debug;
+use std::ops::{Deref, DerefMut};
+
+use crate::{Accumulable, MaybeAccumulable};
+
+/// Wraps an `Lhs` to check the [`MaybeAccumulable`] contract: a
+/// `maybe_accumulate_from` returning `false` must leave `self` untouched.
+///
+/// Every call snapshots the value first and panics with both states when a
+/// rejection changed it. Meant for tests, as in
+/// `partially_accumulate::<Checked<Lhs>>()`; since `Checked<T>` is only
+/// `From<T>`, items of another type are mapped to `Lhs` first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Checked<T>(pub T);
+
+impl<T> Checked<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Checked<T> {
+    fn from(value: T) -> Self {
+        Checked(value)
+    }
+}
+
+impl<T> Deref for Checked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Checked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T, Rhs> Accumulable<Rhs> for Checked<T>
+where
+    T: Accumulable<Rhs>,
+{
+    fn accumulate_from(&mut self, rhs: &Rhs) {
+        self.0.accumulate_from(rhs);
+    }
+}
+
+impl<T, Rhs> MaybeAccumulable<Rhs> for Checked<T>
+where
+    T: MaybeAccumulable<Rhs> + Clone + PartialEq + Debug,
+    Rhs: Debug,
+{
+    fn maybe_accumulate_from(&mut self, rhs: &Rhs) -> bool {
+        let before = self.0.clone();
+
+        let accumulated = self.0.maybe_accumulate_from(rhs);
+
+        if !accumulated && self.0 != before {
+            panic!(
+                "maybe_accumulate_from rejected {rhs:?} but mutated self from {before:?} to {:?}",
+                self.0
+            );
+        }
+
+        accumulated
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::iter::PartiallyAccumulate;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    impl MaybeAccumulable for Volume {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            if self.0 + rhs.0 > 100 {
+                return false;
+            }
+
+            self.accumulate_from(rhs);
+
+            true
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Overeager(u64);
+
+    impl MaybeAccumulable for Overeager {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            self.0 += rhs.0;
+
+            self.0 <= 100
+        }
+    }
+
+    #[test]
+    fn checked() {
+        let volumes = [60, 30, 15, 40, 70].map(Volume);
+
+        let partially_accumulated = volumes
+            .into_iter()
+            .partially_accumulate::<Checked<Volume>>()
+            .map(Checked::into_inner)
+            .collect::<Vec<_>>();
+
+        assert_eq!(partially_accumulated, [90, 55, 70].map(Volume).to_vec());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "rejected Overeager(50) but mutated self from Overeager(60) to Overeager(110)"
+    )]
+    fn checked_rejection_mutates() {
+        let volumes = [60, 50].map(Overeager);
+
+        volumes
+            .into_iter()
+            .partially_accumulate::<Checked<Overeager>>()
+            .for_each(drop);
+    }
+}
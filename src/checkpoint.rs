@@ -0,0 +1,606 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use pin_project::pin_project;
+
+use futures::ready;
+use futures::stream::{FusedStream, Stream};
+
+use crate::stream::PartiallyAccumulated;
+use crate::MaybeAccumulable;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint<Lhs> {
+    /// Number of upstream items consumed, including those in `lhs`.
+    pub offset: u64,
+    pub lhs: Option<Lhs>,
+}
+
+pub trait CheckpointStore<Lhs> {
+    fn load(&mut self) -> io::Result<Option<Checkpoint<Lhs>>>;
+
+    fn save(&mut self, offset: u64, lhs: Option<&Lhs>) -> io::Result<()>;
+}
+
+pub trait Persist: Sized {
+    fn persist<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+
+    fn restore<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+/// Keeps the checkpoint in a single file, replaced atomically on every save.
+///
+/// Both the file and its directory are synced before `save` returns, so a
+/// saved checkpoint survives a crash.
+#[derive(Clone, Debug)]
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn tmp_path(&self) -> PathBuf {
+        let mut name = self
+            .path
+            .file_name()
+            .map(OsString::from)
+            .unwrap_or_default();
+
+        name.push(".tmp");
+
+        self.path.with_file_name(name)
+    }
+}
+
+impl<Lhs> CheckpointStore<Lhs> for FileStore
+where
+    Lhs: Persist,
+{
+    fn load(&mut self) -> io::Result<Option<Checkpoint<Lhs>>> {
+        let mut reader = match File::open(&self.path) {
+            Ok(file) => BufReader::new(file),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut offset = [0; 8];
+        let mut tag = [0; 1];
+
+        reader.read_exact(&mut offset)?;
+        reader.read_exact(&mut tag)?;
+
+        let lhs = match tag[0] {
+            0 => None,
+            1 => Some(Lhs::restore(&mut reader)?),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "corrupted checkpoint",
+                ))
+            }
+        };
+
+        Ok(Some(Checkpoint {
+            offset: u64::from_le_bytes(offset),
+            lhs,
+        }))
+    }
+
+    fn save(&mut self, offset: u64, lhs: Option<&Lhs>) -> io::Result<()> {
+        let tmp_path = self.tmp_path();
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+
+        writer.write_all(&offset.to_le_bytes())?;
+
+        match lhs {
+            Some(lhs) => {
+                writer.write_all(&[1])?;
+                lhs.persist(&mut writer)?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+
+        writer.into_inner()?.sync_all()?;
+
+        fs::rename(tmp_path, &self.path)?;
+
+        sync_parent(&self.path)
+    }
+}
+
+/// Syncs the directory containing `path`, making a rename into it durable.
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+
+    File::open(parent)?.sync_all()
+}
+
+/// Directories can't be opened as files here, so renames are left to the OS.
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Clones share the same checkpoint, so a store can outlive the stream using it.
+#[derive(Debug)]
+pub struct MemoryStore<Lhs> {
+    checkpoint: Arc<Mutex<Option<Checkpoint<Lhs>>>>,
+}
+
+impl<Lhs> MemoryStore<Lhs> {
+    pub fn new() -> Self {
+        Self {
+            checkpoint: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl<Lhs> Default for MemoryStore<Lhs> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Lhs> Clone for MemoryStore<Lhs> {
+    fn clone(&self) -> Self {
+        Self {
+            checkpoint: self.checkpoint.clone(),
+        }
+    }
+}
+
+impl<Lhs> CheckpointStore<Lhs> for MemoryStore<Lhs>
+where
+    Lhs: Clone,
+{
+    fn load(&mut self) -> io::Result<Option<Checkpoint<Lhs>>> {
+        Ok(self.checkpoint.lock().unwrap().clone())
+    }
+
+    fn save(&mut self, offset: u64, lhs: Option<&Lhs>) -> io::Result<()> {
+        *self.checkpoint.lock().unwrap() = Some(Checkpoint {
+            offset,
+            lhs: lhs.cloned(),
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckpointPolicy {
+    /// Checkpoints once `n` upstream items have been consumed since the last
+    /// checkpoint, even in the middle of a group.
+    EveryItems(u64),
+    /// Checkpoints after each emitted group.
+    OnEmit,
+}
+
+/// Counts upstream items, pausing once `every` items have been consumed since
+/// `saved`.
+///
+/// A paused poll returns `Pending` without registering a waker: the caller
+/// must checkpoint and poll again right away.
+#[pin_project]
+pub(crate) struct Counted<S> {
+    #[pin]
+    stream: S,
+    offset: u64,
+    saved: u64,
+    every: Option<u64>,
+}
+
+impl<S> Counted<S> {
+    fn is_paused(&self) -> bool {
+        self.every
+            .is_some_and(|every| self.offset - self.saved >= every)
+    }
+}
+
+impl<S> Stream for Counted<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.is_paused() {
+            return Poll::Pending;
+        }
+
+        let proj = self.project();
+
+        let item = ready!(proj.stream.poll_next(cx));
+
+        if item.is_some() {
+            *proj.offset += 1;
+        }
+
+        Poll::Ready(item)
+    }
+}
+
+/// Partial accumulation that persists its open group and upstream offset.
+///
+/// A checkpoint is only written once the consumer polls again after an emitted
+/// group, so a crash replays that group instead of losing it (at-least-once).
+#[pin_project]
+pub struct Checkpointed<S, Lhs, C> {
+    #[pin]
+    inner: PartiallyAccumulated<Counted<S>, Lhs>,
+    store: C,
+    policy: CheckpointPolicy,
+    emitted: bool,
+}
+
+impl<S, Lhs, C> Checkpointed<S, Lhs, C>
+where
+    C: CheckpointStore<Lhs>,
+{
+    /// Loads the last checkpoint from `store` and builds upstream from its offset.
+    pub fn resume<F>(mut store: C, policy: CheckpointPolicy, stream: F) -> io::Result<Self>
+    where
+        F: FnOnce(u64) -> S,
+    {
+        let (offset, lhs) = match store.load()? {
+            Some(checkpoint) => (checkpoint.offset, checkpoint.lhs),
+            None => (0, None),
+        };
+
+        let stream = Counted {
+            stream: stream(offset),
+            offset,
+            saved: offset,
+            every: match policy {
+                CheckpointPolicy::EveryItems(n) => Some(n.max(1)),
+                CheckpointPolicy::OnEmit => None,
+            },
+        };
+
+        Ok(Self {
+            inner: PartiallyAccumulated::restore(stream, lhs),
+            store,
+            policy,
+            emitted: false,
+        })
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.inner.get_ref().offset
+    }
+
+    fn is_due(&self) -> bool {
+        match self.policy {
+            CheckpointPolicy::EveryItems(_) => self.inner.get_ref().is_paused(),
+            CheckpointPolicy::OnEmit => self.emitted,
+        }
+    }
+
+    fn checkpoint(self: Pin<&mut Self>) -> io::Result<()> {
+        let mut proj = self.project();
+
+        let offset = proj.inner.get_ref().offset;
+
+        proj.store.save(offset, proj.inner.snapshot())?;
+
+        *proj.inner.as_mut().get_pin_mut().project().saved = offset;
+        *proj.emitted = false;
+
+        Ok(())
+    }
+}
+
+impl<S, Lhs, C> FusedStream for Checkpointed<S, Lhs, C>
+where
+    S: Stream,
+    Lhs: From<S::Item> + MaybeAccumulable<S::Item>,
+    C: CheckpointStore<Lhs>,
+{
+    fn is_terminated(&self) -> bool {
+        self.inner.is_terminated()
+    }
+}
+
+impl<S, Lhs, C> Stream for Checkpointed<S, Lhs, C>
+where
+    S: Stream,
+    Lhs: From<S::Item> + MaybeAccumulable<S::Item>,
+    C: CheckpointStore<Lhs>,
+{
+    type Item = io::Result<Lhs>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if self.is_due() {
+                if let Err(err) = self.as_mut().checkpoint() {
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+
+            match self.as_mut().project().inner.poll_next(cx) {
+                Poll::Ready(Some(lhs)) => {
+                    *self.project().emitted = true;
+
+                    return Poll::Ready(Some(Ok(lhs)));
+                }
+                Poll::Ready(None) => {
+                    return match self.checkpoint() {
+                        Ok(()) => Poll::Ready(None),
+                        Err(err) => Poll::Ready(Some(Err(err))),
+                    }
+                }
+                Poll::Pending => {
+                    // A paused upstream resumes once checkpointed.
+                    if !self.is_due() {
+                        return Poll::Pending;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream::{self, StreamExt, TryStreamExt};
+
+    use crate::Accumulable;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum VolumeSize<const N: u64> {
+        Large(Volume),
+        Small(Volume),
+    }
+
+    impl<const N: u64> VolumeSize<N> {
+        pub fn new(volume: Volume) -> Self {
+            if volume.0 >= N {
+                Self::Large(volume)
+            } else {
+                Self::Small(volume)
+            }
+        }
+
+        pub fn volume_value(&self) -> Volume {
+            match self {
+                Self::Large(x) | Self::Small(x) => *x,
+            }
+        }
+    }
+
+    impl<const N: u64> Accumulable for VolumeSize<N> {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = VolumeSize::new(self.volume_value().accumulate(&rhs.volume_value()))
+        }
+    }
+
+    impl<const N: u64> MaybeAccumulable for VolumeSize<N> {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            match self {
+                VolumeSize::Small(_) => {
+                    self.accumulate_from(rhs);
+
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    impl<const N: u64> Persist for VolumeSize<N> {
+        fn persist<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+            writer.write_all(&self.volume_value().0.to_le_bytes())
+        }
+
+        fn restore<R: Read>(reader: &mut R) -> io::Result<Self> {
+            let mut volume = [0; 8];
+
+            reader.read_exact(&mut volume)?;
+
+            Ok(VolumeSize::new(Volume(u64::from_le_bytes(volume))))
+        }
+    }
+
+    type VolumeSize100 = VolumeSize<100>;
+
+    fn volumes() -> Vec<VolumeSize100> {
+        [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1]
+            .into_iter()
+            .map(|volume| VolumeSize100::new(Volume(volume)))
+            .collect()
+    }
+
+    fn upstream(offset: u64) -> impl Stream<Item = VolumeSize100> {
+        stream::iter(volumes().into_iter().skip(offset as usize))
+    }
+
+    #[tokio::test]
+    async fn checkpointed_uninterrupted() {
+        let store = MemoryStore::<VolumeSize100>::new();
+
+        let partially_accumulated =
+            Checkpointed::resume(store.clone(), CheckpointPolicy::OnEmit, upstream)
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+
+        assert_eq!(
+            partially_accumulated,
+            vec![
+                VolumeSize100::Large(Volume(105)),
+                VolumeSize100::Large(Volume(110)),
+                VolumeSize100::Large(Volume(100)),
+                VolumeSize100::Small(Volume(2))
+            ]
+        );
+
+        assert_eq!(
+            CheckpointStore::<VolumeSize100>::load(&mut store.clone()).unwrap(),
+            Some(Checkpoint {
+                offset: 12,
+                lhs: None
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn checkpointed_replays_unacknowledged_group() {
+        let store = MemoryStore::<VolumeSize100>::new();
+
+        let mut partially_accumulated =
+            Checkpointed::resume(store.clone(), CheckpointPolicy::OnEmit, upstream).unwrap();
+
+        let before_crash = vec![
+            partially_accumulated.next().await.unwrap().unwrap(),
+            partially_accumulated.next().await.unwrap().unwrap(),
+        ];
+
+        drop(partially_accumulated);
+
+        let after_crash = Checkpointed::resume(store, CheckpointPolicy::OnEmit, upstream)
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            before_crash,
+            vec![
+                VolumeSize100::Large(Volume(105)),
+                VolumeSize100::Large(Volume(110)),
+            ]
+        );
+
+        assert_eq!(
+            after_crash,
+            vec![
+                VolumeSize100::Large(Volume(110)),
+                VolumeSize100::Large(Volume(100)),
+                VolumeSize100::Small(Volume(2))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn checkpointed_every_items() {
+        let mut store = MemoryStore::<VolumeSize100>::new();
+
+        let (mut sender, receiver) = futures::channel::mpsc::unbounded();
+
+        let mut partially_accumulated =
+            Checkpointed::resume(store.clone(), CheckpointPolicy::EveryItems(2), |_| receiver)
+                .unwrap();
+
+        sender.start_send(VolumeSize100::new(Volume(10))).unwrap();
+        sender.start_send(VolumeSize100::new(Volume(20))).unwrap();
+
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        assert_eq!(
+            store.load().unwrap(),
+            Some(Checkpoint {
+                offset: 2,
+                lhs: Some(VolumeSize100::Small(Volume(30)))
+            })
+        );
+    }
+
+    #[derive(Default)]
+    pub struct Recording(Vec<Checkpoint<VolumeSize100>>);
+
+    impl CheckpointStore<VolumeSize100> for Recording {
+        fn load(&mut self) -> io::Result<Option<Checkpoint<VolumeSize100>>> {
+            Ok(self.0.last().cloned())
+        }
+
+        fn save(&mut self, offset: u64, lhs: Option<&VolumeSize100>) -> io::Result<()> {
+            self.0.push(Checkpoint {
+                offset,
+                lhs: lhs.cloned(),
+            });
+
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn checkpointed_every_items_within_group() {
+        let upstream = stream::iter([1; 12].map(|volume| VolumeSize100::new(Volume(volume))));
+
+        let mut partially_accumulated = Box::pin(
+            Checkpointed::resume(
+                Recording::default(),
+                CheckpointPolicy::EveryItems(5),
+                |_| upstream,
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            partially_accumulated.next().await.unwrap().unwrap(),
+            VolumeSize100::Small(Volume(12))
+        );
+        assert!(partially_accumulated.next().await.is_none());
+
+        let saves = &partially_accumulated.as_mut().project().store.0;
+
+        assert_eq!(
+            saves.iter().map(|save| save.offset).collect::<Vec<_>>(),
+            vec![5, 10, 12]
+        );
+        assert_eq!(saves[0].lhs, Some(VolumeSize100::Small(Volume(5))));
+        assert_eq!(saves[1].lhs, Some(VolumeSize100::Small(Volume(10))));
+        assert_eq!(saves[2].lhs, None);
+    }
+
+    #[test]
+    fn file_store() {
+        let path =
+            std::env::temp_dir().join(format!("accumulable-checkpoint-{}", std::process::id()));
+
+        let mut store = FileStore::new(&path);
+
+        assert_eq!(
+            CheckpointStore::<VolumeSize100>::load(&mut store).unwrap(),
+            None
+        );
+
+        store
+            .save(7, Some(&VolumeSize100::new(Volume(42))))
+            .unwrap();
+
+        assert_eq!(
+            store.load().unwrap(),
+            Some(Checkpoint {
+                offset: 7,
+                lhs: Some(VolumeSize100::Small(Volume(42)))
+            })
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
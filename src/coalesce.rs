@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use crate::MaybeAccumulable;
+
+/// Partial accumulation within a collection: like `dedup_by`, but adjacent
+/// elements are merged into the first of their group instead of dropped.
+///
+/// Yields the same groups as [`iter::PartiallyAccumulated`](crate::iter::PartiallyAccumulated)
+/// with `Lhs = T`, in O(n) and without allocating.
+pub trait CoalesceInPlace {
+    fn coalesce_in_place(&mut self);
+}
+
+/// Coalesces `slice` into its prefix and returns the prefix length.
+fn coalesce<T>(slice: &mut [T]) -> usize
+where
+    T: MaybeAccumulable,
+{
+    if slice.is_empty() {
+        return 0;
+    }
+
+    let mut write = 0;
+
+    for read in 1..slice.len() {
+        let (head, tail) = slice.split_at_mut(read);
+
+        if !head[write].maybe_accumulate_from(&tail[0]) {
+            write += 1;
+
+            slice.swap(write, read);
+        }
+    }
+
+    write + 1
+}
+
+impl<T> CoalesceInPlace for Vec<T>
+where
+    T: MaybeAccumulable,
+{
+    fn coalesce_in_place(&mut self) {
+        let len = coalesce(self);
+
+        self.truncate(len);
+    }
+}
+
+impl<T> CoalesceInPlace for VecDeque<T>
+where
+    T: MaybeAccumulable,
+{
+    fn coalesce_in_place(&mut self) {
+        let len = coalesce(self.make_contiguous());
+
+        self.truncate(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::iter::PartiallyAccumulate;
+    use crate::Accumulable;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum VolumeSize<const N: u64> {
+        Large(Volume),
+        Small(Volume),
+    }
+
+    impl<const N: u64> VolumeSize<N> {
+        pub fn new(volume: Volume) -> Self {
+            if volume.0 >= N {
+                Self::Large(volume)
+            } else {
+                Self::Small(volume)
+            }
+        }
+
+        pub fn volume_value(&self) -> Volume {
+            match self {
+                Self::Large(x) | Self::Small(x) => *x,
+            }
+        }
+    }
+
+    impl<const N: u64> Accumulable for VolumeSize<N> {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = VolumeSize::new(self.volume_value().accumulate(&rhs.volume_value()))
+        }
+    }
+
+    impl<const N: u64> MaybeAccumulable for VolumeSize<N> {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            match self {
+                VolumeSize::Small(_) => {
+                    self.accumulate_from(rhs);
+
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    type VolumeSize100 = VolumeSize<100>;
+
+    fn volumes() -> Vec<VolumeSize100> {
+        [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1]
+            .map(|volume| VolumeSize100::new(Volume(volume)))
+            .to_vec()
+    }
+
+    #[test]
+    fn coalesce_vec() {
+        let mut volumes = volumes();
+
+        volumes.coalesce_in_place();
+
+        assert_eq!(
+            volumes,
+            vec![
+                VolumeSize100::Large(Volume(105)),
+                VolumeSize100::Large(Volume(110)),
+                VolumeSize100::Large(Volume(100)),
+                VolumeSize100::Small(Volume(2))
+            ]
+        );
+    }
+
+    #[test]
+    fn coalesce_vec_deque() {
+        let mut deque = VecDeque::new();
+
+        for volume in volumes().into_iter().rev() {
+            deque.push_front(volume);
+        }
+
+        deque.coalesce_in_place();
+
+        assert_eq!(
+            Vec::from(deque),
+            volumes()
+                .into_iter()
+                .partially_accumulate::<VolumeSize100>()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn coalesce_empty() {
+        let mut volumes = Vec::<VolumeSize100>::new();
+
+        volumes.coalesce_in_place();
+
+        assert!(volumes.is_empty());
+    }
+}
@@ -0,0 +1,459 @@
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
+
+use pin_project::pin_project;
+
+use futures::future::Future;
+use futures::stream::{FusedStream, Stream, StreamExt};
+use futures::task::AtomicWaker;
+
+use crate::Accumulable;
+
+/// Upstream items [`Conflated`] merges before handing over its pending value.
+const DRAIN_BUDGET: usize = 64;
+
+/// Runs the background task of [`Conflate::conflate_spawned`].
+pub trait Spawner {
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
+}
+
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioSpawner;
+
+#[cfg(feature = "tokio")]
+impl Spawner for TokioSpawner {
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(future);
+    }
+}
+
+pub trait Conflate<Rhs>: Sized {
+    fn conflate<Lhs>(self) -> Conflated<Self, Lhs>
+    where
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+
+    /// Drains upstream eagerly in a task spawned on `spawner`, so items keep
+    /// being merged while the consumer is busy, and hands over the pending
+    /// value whenever the returned [`Receiver`] is polled.
+    fn conflate_spawned<Lhs, Sp>(self, spawner: &Sp) -> Receiver<Lhs>
+    where
+        Self: Send + 'static,
+        Lhs: From<Rhs> + Accumulable<Rhs> + Send + 'static,
+        Sp: Spawner;
+}
+
+impl<S> Conflate<S::Item> for S
+where
+    S: Stream,
+{
+    #[inline]
+    fn conflate<Lhs>(self) -> Conflated<S, Lhs>
+    where
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        Conflated::new(self)
+    }
+
+    fn conflate_spawned<Lhs, Sp>(self, spawner: &Sp) -> Receiver<Lhs>
+    where
+        Self: Send + 'static,
+        Lhs: From<S::Item> + Accumulable<S::Item> + Send + 'static,
+        Sp: Spawner,
+    {
+        let (sender, receiver) = channel();
+
+        spawner.spawn(sender.forward(self));
+
+        receiver
+    }
+}
+
+/// Merges everything upstream has ready into one pending value, handed over
+/// whenever the consumer polls.
+///
+/// At most a fixed budget of items is merged per poll, so an upstream that is
+/// always ready still lets the consumer and the executor make progress.
+///
+/// Upstream is only driven while the consumer polls; to keep draining it in
+/// between, use [`Conflate::conflate_spawned`] instead.
+#[pin_project]
+pub struct Conflated<S, Lhs> {
+    #[pin]
+    stream: S,
+    pending: Option<Lhs>,
+    terminated: bool,
+}
+
+impl<S, Lhs> Conflated<S, Lhs> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            pending: None,
+            terminated: false,
+        }
+    }
+}
+
+impl<S, Lhs> FusedStream for Conflated<S, Lhs>
+where
+    S: Stream,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.pending.is_none()
+    }
+}
+
+impl<S, Lhs> Stream for Conflated<S, Lhs>
+where
+    S: Stream,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    type Item = Lhs;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+        let mut budget = DRAIN_BUDGET;
+
+        while !*proj.terminated && budget > 0 {
+            budget -= 1;
+
+            match proj.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(rhs)) => match proj.pending {
+                    Some(pending) => pending.accumulate_from(&rhs),
+                    None => *proj.pending = Some(Lhs::from(rhs)),
+                },
+                Poll::Ready(None) => *proj.terminated = true,
+                Poll::Pending => break,
+            }
+        }
+
+        match proj.pending.take() {
+            Some(pending) => Poll::Ready(Some(pending)),
+            None if *proj.terminated => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+struct State<Lhs> {
+    pending: Option<Lhs>,
+    senders: usize,
+    receiver: bool,
+}
+
+struct Shared<Lhs> {
+    state: Mutex<State<Lhs>>,
+    condvar: Condvar,
+    waker: AtomicWaker,
+}
+
+impl<Lhs> Shared<Lhs> {
+    fn notify(&self) {
+        self.condvar.notify_one();
+        self.waker.wake();
+    }
+}
+
+/// A conflating channel: sends never block, and merge into the value pending
+/// for the receiver.
+pub fn channel<Lhs>() -> (Sender<Lhs>, Receiver<Lhs>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            pending: None,
+            senders: 1,
+            receiver: true,
+        }),
+        condvar: Condvar::new(),
+        waker: AtomicWaker::new(),
+    });
+
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+pub struct Sender<Lhs> {
+    shared: Arc<Shared<Lhs>>,
+}
+
+impl<Lhs> Sender<Lhs> {
+    /// Merges `rhs` into the pending value, or gives it back if the receiver
+    /// is gone.
+    pub fn send<Rhs>(&self, rhs: Rhs) -> Result<(), Rhs>
+    where
+        Lhs: From<Rhs> + Accumulable<Rhs>,
+    {
+        let mut state = self.shared.state.lock().unwrap();
+
+        if !state.receiver {
+            return Err(rhs);
+        }
+
+        match &mut state.pending {
+            Some(pending) => pending.accumulate_from(&rhs),
+            None => state.pending = Some(Lhs::from(rhs)),
+        }
+
+        drop(state);
+
+        self.shared.notify();
+
+        Ok(())
+    }
+
+    /// Sends every item of `stream`, until it ends or the receiver is gone.
+    pub async fn forward<S>(self, stream: S)
+    where
+        S: Stream,
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        let mut stream = std::pin::pin!(stream);
+
+        while let Some(rhs) = stream.next().await {
+            if self.send(rhs).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+impl<Lhs> Clone for Sender<Lhs> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<Lhs> Drop for Sender<Lhs> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        state.senders -= 1;
+
+        if state.senders == 0 {
+            drop(state);
+
+            self.shared.notify();
+        }
+    }
+}
+
+pub struct Receiver<Lhs> {
+    shared: Arc<Shared<Lhs>>,
+}
+
+impl<Lhs> Receiver<Lhs> {
+    /// Blocks until a value is pending, or returns `None` once every sender is
+    /// gone and nothing is left.
+    pub fn recv(&self) -> Option<Lhs> {
+        let mut state = self.shared.state.lock().unwrap();
+
+        loop {
+            if let Some(pending) = state.pending.take() {
+                return Some(pending);
+            }
+
+            if state.senders == 0 {
+                return None;
+            }
+
+            state = self.shared.condvar.wait(state).unwrap();
+        }
+    }
+
+    pub fn try_recv(&self) -> Option<Lhs> {
+        self.shared.state.lock().unwrap().pending.take()
+    }
+}
+
+impl<Lhs> Drop for Receiver<Lhs> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+
+        state.receiver = false;
+        state.pending = None;
+    }
+}
+
+impl<Lhs> FusedStream for Receiver<Lhs> {
+    fn is_terminated(&self) -> bool {
+        let state = self.shared.state.lock().unwrap();
+
+        state.senders == 0 && state.pending.is_none()
+    }
+}
+
+impl<Lhs> Stream for Receiver<Lhs> {
+    type Item = Lhs;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.shared.waker.register(cx.waker());
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        match state.pending.take() {
+            Some(pending) => Poll::Ready(Some(pending)),
+            None if state.senders == 0 => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use futures::channel::mpsc;
+    use futures::{stream, SinkExt};
+
+    use super::*;
+
+    /// Tokio's spawner, available to tests built without the `tokio` feature.
+    struct TestSpawner;
+
+    impl Spawner for TestSpawner {
+        fn spawn<F>(&self, future: F)
+        where
+            F: Future<Output = ()> + Send + 'static,
+        {
+            tokio::spawn(future);
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn conflate_ready() {
+        let volumes = stream::iter([Volume(10), Volume(15), Volume(20)]);
+
+        let conflated = volumes.conflate::<Volume>().collect::<Vec<_>>().await;
+
+        assert_eq!(conflated, vec![Volume(45)])
+    }
+
+    #[tokio::test]
+    async fn conflate_always_ready() {
+        let mut conflated = stream::repeat(Volume(1)).conflate::<Volume>();
+
+        assert_eq!(conflated.next().await, Some(Volume(DRAIN_BUDGET as u64)));
+        assert_eq!(conflated.next().await, Some(Volume(DRAIN_BUDGET as u64)));
+
+        let selected = stream::select(conflated, stream::iter([Volume(0)]))
+            .take(3)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(selected.contains(&Volume(0)));
+    }
+
+    #[tokio::test]
+    async fn conflate_slow_consumer() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut conflated = receiver.conflate::<Volume>();
+
+        assert!(futures::poll!(conflated.next()).is_pending());
+
+        sender.unbounded_send(Volume(10)).unwrap();
+        sender.unbounded_send(Volume(15)).unwrap();
+
+        assert_eq!(conflated.next().await, Some(Volume(25)));
+
+        sender.unbounded_send(Volume(20)).unwrap();
+        drop(sender);
+
+        assert_eq!(conflated.collect::<Vec<_>>().await, vec![Volume(20)]);
+    }
+
+    #[tokio::test]
+    async fn conflate_spawned_slow_consumer() {
+        let (mut sender, receiver) = mpsc::channel(0);
+
+        let conflated = receiver.conflate_spawned::<Volume, _>(&TestSpawner);
+
+        // Without a task draining upstream, the second send would never finish.
+        for volume in 1..=100 {
+            sender.send(Volume(volume)).await.unwrap();
+        }
+
+        drop(sender);
+
+        let conflated = conflated.collect::<Vec<_>>().await;
+
+        assert!(conflated.len() <= 2);
+        assert_eq!(
+            conflated
+                .into_iter()
+                .reduce(|lhs, rhs| lhs.accumulate(&rhs)),
+            Some(Volume(5050))
+        );
+    }
+
+    #[test]
+    fn channel_threads() {
+        let (sender, receiver) = channel::<Volume>();
+
+        let producer = thread::spawn(move || {
+            for volume in 1..=100 {
+                sender.send(Volume(volume)).unwrap();
+            }
+        });
+
+        let mut total = Volume(0);
+
+        while let Some(volume) = receiver.recv() {
+            total.accumulate_from(&volume);
+        }
+
+        producer.join().unwrap();
+
+        assert_eq!(total, Volume(5050));
+    }
+
+    #[test]
+    fn channel_receiver_dropped() {
+        let (sender, receiver) = channel::<Volume>();
+
+        sender.send(Volume(10)).unwrap();
+        sender.send(Volume(15)).unwrap();
+
+        assert_eq!(receiver.try_recv(), Some(Volume(25)));
+        assert_eq!(receiver.try_recv(), None);
+
+        drop(receiver);
+
+        assert_eq!(sender.send(Volume(20)), Err(Volume(20)));
+    }
+
+    #[tokio::test]
+    async fn channel_forward() {
+        let (sender, receiver) = channel::<Volume>();
+
+        let volumes = stream::iter([Volume(10), Volume(15), Volume(20)]);
+
+        tokio::spawn(sender.forward(volumes)).await.unwrap();
+
+        assert_eq!(receiver.collect::<Vec<_>>().await, vec![Volume(45)]);
+    }
+}
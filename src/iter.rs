@@ -1,7 +1,15 @@
-use std::iter::Peekable;
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::iter::{Fuse, Peekable};
 use std::marker::PhantomData;
+use std::ops::Range;
 
-use crate::{Accumulable, MaybeAccumulable};
+use crate::time::{SpanOf, Timestamp, TimestampOf};
+use crate::window::{Deaccumulate, PaneCombine, Panes, Recombine};
+use crate::{
+    Accumulable, Associative, Commutative, Deaccumulable, Fit, MaybeAccumulable,
+    MaybeAccumulableLookahead, Splittable, Timestamped, Weighable,
+};
 
 pub trait Accumulate<Rhs> {
     fn accumulate<Lhs>(self) -> Option<Lhs>
@@ -24,6 +32,56 @@ where
     }
 }
 
+pub trait TreeAccumulate<Rhs>: Iterator<Item = Rhs> {
+    /// Accumulates pairwise, merging values built from equally many items, so
+    /// that each item takes part in O(log n) merges instead of up to n. Holds
+    /// O(log n) partial values; equal to [`accumulate`](Accumulate::accumulate)
+    /// for [`Associative`] accumulators up to rounding.
+    fn tree_accumulate<Lhs>(self) -> Option<Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Associative;
+}
+
+impl<I> TreeAccumulate<I::Item> for I
+where
+    I: Iterator,
+{
+    fn tree_accumulate<Lhs>(self) -> Option<Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Associative,
+    {
+        let mut stack: Vec<(u32, Lhs)> = Vec::new();
+
+        for rhs in self {
+            let mut level = 0;
+            let mut lhs = Lhs::from(rhs);
+
+            while let Some((top, _)) = stack.last() {
+                if *top != level {
+                    break;
+                }
+
+                let (_, earlier) = stack.pop().unwrap();
+
+                lhs = earlier.accumulate(&lhs);
+                level += 1;
+            }
+
+            stack.push((level, lhs));
+        }
+
+        let (_, mut lhs) = stack.pop()?;
+
+        while let Some((_, earlier)) = stack.pop() {
+            lhs = earlier.accumulate(&lhs);
+        }
+
+        Some(lhs)
+    }
+}
+
 pub trait PartiallyAccumulate<Rhs>: Iterator {
     fn partially_accumulate<Lhs>(self) -> PartiallyAccumulated<Self, Lhs>
     where
@@ -45,11 +103,27 @@ where
     }
 }
 
+/// Yields maximal runs of items merged with
+/// [`maybe_accumulate_from`](MaybeAccumulable::maybe_accumulate_from).
+///
+/// Iterating from the back builds groups backwards: each group starts from its
+/// last item and earlier items are merged into it, so an incomplete group ends
+/// up at the front.
+///
+/// When both ends are used, each end builds its groups on its own and every
+/// item is consumed by exactly one of them. An item already pulled by one end
+/// is never merged into a group of the other, so groups never span the point
+/// where the ends meet: mixing `next` and `next_back` can yield different
+/// groups than iterating in a single direction, a run that crosses the meeting
+/// point being split in two.
 pub struct PartiallyAccumulated<I, Lhs>
 where
     I: Iterator,
 {
-    iter: Peekable<I>,
+    iter: Fuse<I>,
+    front: Option<I::Item>,
+    back: Option<I::Item>,
+    max_items: usize,
     _lhs: PhantomData<Lhs>,
 }
 
@@ -59,10 +133,28 @@ where
 {
     pub fn new(iter: I) -> Self {
         Self {
-            iter: iter.peekable(),
+            iter: iter.fuse(),
+            front: None,
+            back: None,
+            max_items: usize::MAX,
             _lhs: PhantomData,
         }
     }
+
+    /// Closes a group once it holds `max_items` items, whatever
+    /// `maybe_accumulate_from` would decide.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        assert!(max_items > 0, "max_items must be positive");
+
+        self.max_items = max_items;
+
+        self
+    }
+
+    /// Closes a group once `max_merges` items have been merged into its first.
+    pub fn max_merges(self, max_merges: usize) -> Self {
+        self.max_items(max_merges.saturating_add(1))
+    }
 }
 
 impl<I, Lhs> Iterator for PartiallyAccumulated<I, Lhs>
@@ -73,12 +165,25 @@ where
     type Item = Lhs;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut lhs = Lhs::from(self.iter.next()?);
+        let first = self
+            .front
+            .take()
+            .or_else(|| self.iter.next())
+            .or_else(|| self.back.take())?;
+
+        let mut lhs = Lhs::from(first);
+        let mut items = 1;
+
+        while items < self.max_items {
+            let Some(rhs) = self.iter.next() else {
+                break;
+            };
 
-        while let Some(rhs) = self.iter.peek() {
-            if lhs.maybe_accumulate_from(rhs) {
-                drop(self.iter.next());
+            if lhs.maybe_accumulate_from(&rhs) {
+                items += 1;
             } else {
+                self.front = Some(rhs);
+
                 break;
             }
         }
@@ -87,150 +192,1641 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[derive(Clone, Copy, Debug, PartialEq)]
-    pub struct Volume(u64);
+impl<I, Lhs> DoubleEndedIterator for PartiallyAccumulated<I, Lhs>
+where
+    I: DoubleEndedIterator,
+    Lhs: From<I::Item> + MaybeAccumulable<I::Item>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let last = self
+            .back
+            .take()
+            .or_else(|| self.iter.next_back())
+            .or_else(|| self.front.take())?;
 
-    impl Accumulable for Volume {
-        fn accumulate_from(&mut self, rhs: &Self) {
-            *self = Volume(self.0 + rhs.0);
-        }
-    }
+        let mut lhs = Lhs::from(last);
+        let mut items = 1;
 
-    #[test]
-    fn test_accumulate_zero() {
-        let volumes = [];
+        while items < self.max_items {
+            let Some(rhs) = self.iter.next_back() else {
+                break;
+            };
 
-        assert_eq!(volumes.into_iter().accumulate::<Volume>(), None);
-    }
+            if lhs.maybe_accumulate_from(&rhs) {
+                items += 1;
+            } else {
+                self.back = Some(rhs);
 
-    #[test]
-    fn test_accumulate_one() {
-        let volumes = [Volume(10)];
+                break;
+            }
+        }
 
-        assert_eq!(
-            volumes.into_iter().accumulate::<Volume>().unwrap(),
-            Volume(10)
-        );
+        Some(lhs)
     }
+}
 
-    #[test]
-    fn test_accumulate() {
-        let volumes = [Volume(10), Volume(15), Volume(20), Volume(25), Volume(30)];
+pub trait PartiallyAccumulateLookahead<Rhs>: Iterator<Item = Rhs> {
+    fn partially_accumulate_lookahead<Lhs>(
+        self,
+        lookahead: usize,
+    ) -> PartiallyAccumulatedLookahead<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + MaybeAccumulableLookahead<Rhs>;
+}
 
-        assert_eq!(
-            volumes.into_iter().accumulate::<Volume>().unwrap(),
-            Volume(100)
-        );
+impl<I> PartiallyAccumulateLookahead<I::Item> for I
+where
+    I: Iterator,
+{
+    #[inline]
+    fn partially_accumulate_lookahead<Lhs>(
+        self,
+        lookahead: usize,
+    ) -> PartiallyAccumulatedLookahead<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + MaybeAccumulableLookahead<I::Item>,
+    {
+        PartiallyAccumulatedLookahead::new(self, lookahead)
     }
+}
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
-    pub enum VolumeSize<const N: u64> {
-        Large(Volume),
-        Small(Volume),
+/// Partial accumulation whose decisions see up to `lookahead` items after the
+/// candidate; fewer only once the input runs out.
+pub struct PartiallyAccumulatedLookahead<I, Lhs>
+where
+    I: Iterator,
+{
+    iter: Fuse<I>,
+    buffer: VecDeque<I::Item>,
+    lookahead: usize,
+    _lhs: PhantomData<Lhs>,
+}
+
+impl<I, Lhs> PartiallyAccumulatedLookahead<I, Lhs>
+where
+    I: Iterator,
+{
+    pub fn new(iter: I, lookahead: usize) -> Self {
+        Self {
+            iter: iter.fuse(),
+            buffer: VecDeque::with_capacity(lookahead.saturating_add(1)),
+            lookahead,
+            _lhs: PhantomData,
+        }
     }
 
-    impl<const N: u64> VolumeSize<N> {
-        pub fn new(volume: Volume) -> Self {
-            if volume.0 >= N {
-                Self::Large(volume)
-            } else {
-                Self::Small(volume)
+    fn fill(&mut self) {
+        while self.buffer.len() <= self.lookahead {
+            match self.iter.next() {
+                Some(rhs) => self.buffer.push_back(rhs),
+                None => break,
             }
         }
+    }
+}
 
-        pub fn volume_value(&self) -> Volume {
-            match self {
-                Self::Large(x) | Self::Small(x) => *x,
+impl<I, Lhs> Iterator for PartiallyAccumulatedLookahead<I, Lhs>
+where
+    I: Iterator,
+    Lhs: From<I::Item> + MaybeAccumulableLookahead<I::Item>,
+{
+    type Item = Lhs;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.fill();
+
+        let mut lhs = Lhs::from(self.buffer.pop_front()?);
+
+        loop {
+            self.fill();
+
+            match self.buffer.make_contiguous().split_first() {
+                Some((rhs, lookahead)) if lhs.maybe_accumulate_from_lookahead(rhs, lookahead) => {
+                    drop(self.buffer.pop_front());
+                }
+                _ => break,
             }
         }
+
+        Some(lhs)
     }
+}
 
-    impl<const N: u64> Accumulable for VolumeSize<N> {
-        fn accumulate_from(&mut self, rhs: &Self) {
-            *self = VolumeSize::new(self.volume_value().accumulate(&rhs.volume_value()))
+pub trait AccumulateUpToWeight<Rhs>: Iterator<Item = Rhs>
+where
+    Rhs: Weighable,
+{
+    fn accumulate_up_to_weight<Lhs>(self, max_weight: usize) -> AccumulatedUpToWeight<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<I> AccumulateUpToWeight<I::Item> for I
+where
+    I: Iterator,
+    I::Item: Weighable,
+{
+    #[inline]
+    fn accumulate_up_to_weight<Lhs>(self, max_weight: usize) -> AccumulatedUpToWeight<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Accumulable<I::Item>,
+    {
+        AccumulatedUpToWeight::new(self, max_weight)
+    }
+}
+
+/// Groups consecutive items while their total weight stays within
+/// `max_weight`; an item heavier than that on its own is yielded alone.
+pub struct AccumulatedUpToWeight<I, Lhs>
+where
+    I: Iterator,
+{
+    iter: Peekable<I>,
+    max_weight: usize,
+    max_items: usize,
+    _lhs: PhantomData<Lhs>,
+}
+
+impl<I, Lhs> AccumulatedUpToWeight<I, Lhs>
+where
+    I: Iterator,
+{
+    pub fn new(iter: I, max_weight: usize) -> Self {
+        Self {
+            iter: iter.peekable(),
+            max_weight,
+            max_items: usize::MAX,
+            _lhs: PhantomData,
         }
     }
 
-    impl<const N: u64> MaybeAccumulable for VolumeSize<N> {
-        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
-            match self {
-                VolumeSize::Small(_) => {
-                    self.accumulate_from(rhs);
+    /// Also closes a group once it holds `max_items` items.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        assert!(max_items > 0, "max_items must be positive");
 
-                    true
+        self.max_items = max_items;
+
+        self
+    }
+}
+
+impl<I, Lhs> Iterator for AccumulatedUpToWeight<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Weighable,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+{
+    type Item = Lhs;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.iter.next()?;
+
+        let mut weight = first.weight();
+        let mut items = 1;
+        let mut lhs = Lhs::from(first);
+
+        while weight < self.max_weight && items < self.max_items {
+            match self.iter.peek() {
+                Some(rhs) if weight.saturating_add(rhs.weight()) <= self.max_weight => {
+                    weight += rhs.weight();
+                    items += 1;
+
+                    lhs.accumulate_from(rhs);
+
+                    drop(self.iter.next());
                 }
-                _ => false,
+                _ => break,
             }
         }
+
+        Some(lhs)
     }
+}
 
-    #[test]
-    fn partially_accumulate_zero() {
-        type VolumeSize100 = VolumeSize<100>;
+pub trait PartiallyAccumulateExact<Rhs>: Iterator<Item = Rhs>
+where
+    Rhs: Weighable + Splittable,
+{
+    fn partially_accumulate_exact<Lhs>(
+        self,
+        capacity: usize,
+    ) -> PartiallyAccumulatedExact<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
 
-        let volumes = [];
+impl<I> PartiallyAccumulateExact<I::Item> for I
+where
+    I: Iterator,
+    I::Item: Weighable + Splittable,
+{
+    #[inline]
+    fn partially_accumulate_exact<Lhs>(
+        self,
+        capacity: usize,
+    ) -> PartiallyAccumulatedExact<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Accumulable<I::Item>,
+    {
+        PartiallyAccumulatedExact::new(self, capacity)
+    }
+}
 
-        let partially_accumulated = volumes
-            .into_iter()
-            .partially_accumulate::<VolumeSize100>()
-            .collect::<Vec<_>>();
+pub(crate) struct Exact<Rhs, Lhs> {
+    capacity: usize,
+    weight: usize,
+    lhs: Option<Lhs>,
+    carry: Option<Rhs>,
+}
 
-        assert_eq!(partially_accumulated, vec![])
-    }
+impl<Rhs, Lhs> Exact<Rhs, Lhs>
+where
+    Rhs: Weighable + Splittable,
+    Lhs: From<Rhs> + Accumulable<Rhs>,
+{
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
 
-    #[test]
-    fn partially_accumulate_one() {
-        type VolumeSize100 = VolumeSize<100>;
+        Self {
+            capacity,
+            weight: 0,
+            lhs: None,
+            carry: None,
+        }
+    }
 
-        let volumes = [VolumeSize100::new(Volume(60))];
+    pub fn is_empty(&self) -> bool {
+        self.lhs.is_none() && self.carry.is_none()
+    }
 
-        let partially_accumulated = volumes
-            .into_iter()
-            .partially_accumulate::<VolumeSize100>()
-            .collect::<Vec<_>>();
+    pub fn push(&mut self, rhs: Rhs) {
+        debug_assert!(self.carry.is_none());
 
-        assert_eq!(partially_accumulated, vec![VolumeSize100::Small(Volume(60))])
+        self.carry = Some(rhs);
     }
 
-    #[test]
-    fn partially_accumulate() {
-        type VolumeSize100 = VolumeSize<100>;
+    /// Moves the pending item into the open group, splitting off whatever
+    /// overflows it, and yields the group once it is full.
+    pub fn pop_full(&mut self) -> Option<Lhs> {
+        let mut rhs = self.carry.take()?;
 
-        let volumes = [
-            VolumeSize100::new(Volume(60)),
-            VolumeSize100::new(Volume(30)),
-            VolumeSize100::new(Volume(15)),
-            //
-            VolumeSize100::new(Volume(40)),
-            VolumeSize100::new(Volume(70)),
-            //
-            VolumeSize100::new(Volume(80)),
-            VolumeSize100::new(Volume(10)),
-            VolumeSize100::new(Volume(5)),
-            VolumeSize100::new(Volume(3)),
-            VolumeSize100::new(Volume(2)),
-            //
-            VolumeSize100::new(Volume(1)),
-            VolumeSize100::new(Volume(1)),
-        ];
+        let room = self.capacity - self.weight;
 
-        let partially_accumulated = volumes
-            .into_iter()
-            .partially_accumulate::<VolumeSize100>()
-            .collect::<Vec<_>>();
+        if rhs.weight() > room {
+            let (head, tail) = rhs.split_at(room);
 
-        assert_eq!(
-            partially_accumulated,
-            vec![
+            self.carry = Some(tail);
+
+            rhs = head;
+        }
+
+        self.weight += rhs.weight();
+
+        match &mut self.lhs {
+            Some(lhs) => lhs.accumulate_from(&rhs),
+            None => self.lhs = Some(Lhs::from(rhs)),
+        }
+
+        if self.weight >= self.capacity {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    pub fn flush(&mut self) -> Option<Lhs> {
+        self.weight = 0;
+
+        self.lhs.take()
+    }
+}
+
+/// Groups items into exactly `capacity` of weight each, splitting an item that
+/// overflows a group and carrying the remainder forward. Only the last group
+/// may be lighter.
+pub struct PartiallyAccumulatedExact<I, Lhs>
+where
+    I: Iterator,
+{
+    iter: I,
+    exact: Exact<I::Item, Lhs>,
+}
+
+impl<I, Lhs> PartiallyAccumulatedExact<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Weighable + Splittable,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+{
+    pub fn new(iter: I, capacity: usize) -> Self {
+        Self {
+            iter,
+            exact: Exact::new(capacity),
+        }
+    }
+}
+
+impl<I, Lhs> Iterator for PartiallyAccumulatedExact<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Weighable + Splittable,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+{
+    type Item = Lhs;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(lhs) = self.exact.pop_full() {
+                return Some(lhs);
+            }
+
+            match self.iter.next() {
+                Some(rhs) => self.exact.push(rhs),
+                None => return self.exact.flush(),
+            }
+        }
+    }
+}
+
+pub trait BinPack<Rhs>: Iterator<Item = Rhs>
+where
+    Rhs: Weighable,
+{
+    fn bin_pack<Lhs>(self, capacity: usize, fit: Fit) -> BinPacked<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<I> BinPack<I::Item> for I
+where
+    I: Iterator,
+    I::Item: Weighable,
+{
+    #[inline]
+    fn bin_pack<Lhs>(self, capacity: usize, fit: Fit) -> BinPacked<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Accumulable<I::Item>,
+    {
+        BinPacked::new(self, capacity, fit)
+    }
+}
+
+struct Bin<Lhs> {
+    weight: usize,
+    lhs: Lhs,
+}
+
+pub(crate) struct Bins<Lhs> {
+    capacity: usize,
+    fit: Fit,
+    max_open: usize,
+    open: Vec<Bin<Lhs>>,
+    closed: VecDeque<Lhs>,
+}
+
+impl<Lhs> Bins<Lhs> {
+    pub fn new(capacity: usize, fit: Fit) -> Self {
+        Self {
+            capacity,
+            fit,
+            max_open: usize::MAX,
+            open: Vec::new(),
+            closed: VecDeque::new(),
+        }
+    }
+
+    pub fn set_max_open(&mut self, max_open: usize) {
+        assert!(max_open > 0, "max_open must be positive");
+
+        self.max_open = max_open;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty() && self.closed.is_empty()
+    }
+
+    fn find(&self, weight: usize) -> Option<usize> {
+        let fits = self
+            .open
+            .iter()
+            .enumerate()
+            .filter(|(_, bin)| bin.weight.saturating_add(weight) <= self.capacity);
+
+        match self.fit {
+            Fit::First => fits.map(|(i, _)| i).next(),
+            Fit::Best => fits
+                .min_by_key(|(_, bin)| self.capacity - bin.weight)
+                .map(|(i, _)| i),
+        }
+    }
+
+    /// Packs `rhs` into an open bin, closing bins that become full. When none
+    /// fits and `max_open` bins are open, the fullest is closed first.
+    pub fn push<Rhs>(&mut self, rhs: Rhs)
+    where
+        Rhs: Weighable,
+        Lhs: From<Rhs> + Accumulable<Rhs>,
+    {
+        let weight = rhs.weight();
+
+        if let Some(i) = self.find(weight) {
+            let bin = &mut self.open[i];
+
+            bin.weight += weight;
+            bin.lhs.accumulate_from(&rhs);
+
+            if bin.weight >= self.capacity {
+                self.closed.push_back(self.open.remove(i).lhs);
+            }
+        } else if weight >= self.capacity {
+            self.closed.push_back(Lhs::from(rhs));
+        } else {
+            if self.open.len() >= self.max_open {
+                let fullest = (0..self.open.len())
+                    .rev()
+                    .max_by_key(|&i| self.open[i].weight)
+                    .unwrap();
+
+                self.closed.push_back(self.open.remove(fullest).lhs);
+            }
+
+            self.open.push(Bin {
+                weight,
+                lhs: Lhs::from(rhs),
+            });
+        }
+    }
+
+    pub fn pop_closed(&mut self) -> Option<Lhs> {
+        self.closed.pop_front()
+    }
+
+    pub fn flush(&mut self) {
+        self.closed.extend(self.open.drain(..).map(|bin| bin.lhs));
+    }
+}
+
+/// Packs items, in any order, into as few bins of `capacity` weight as it can.
+/// An item heavier than `capacity` gets a bin of its own.
+///
+/// Bins are yielded as they fill up, then the rest in the order they were
+/// opened once the input is exhausted.
+pub struct BinPacked<I, Lhs> {
+    iter: Option<I>,
+    bins: Bins<Lhs>,
+    decreasing: bool,
+}
+
+impl<I, Lhs> BinPacked<I, Lhs> {
+    pub fn new(iter: I, capacity: usize, fit: Fit) -> Self {
+        Self {
+            iter: Some(iter),
+            bins: Bins::new(capacity, fit),
+            decreasing: false,
+        }
+    }
+
+    /// Packs the heaviest items first, e.g. first-fit decreasing.
+    pub fn decreasing(mut self) -> Self {
+        self.decreasing = true;
+
+        self
+    }
+}
+
+impl<I, Lhs> Iterator for BinPacked<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Weighable,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+{
+    type Item = Lhs;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(iter) = self.iter.take() {
+            if self.decreasing {
+                let mut items = iter.collect::<Vec<_>>();
+
+                items.sort_by_key(|rhs| Reverse(rhs.weight()));
+
+                items.into_iter().for_each(|rhs| self.bins.push(rhs));
+            } else {
+                iter.for_each(|rhs| self.bins.push(rhs));
+            }
+
+            self.bins.flush();
+        }
+
+        self.bins.pop_closed()
+    }
+}
+
+pub trait TumblingWindow<Rhs>: Iterator<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn tumbling_window<Lhs>(self, size: SpanOf<Rhs>) -> TumblingWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<I> TumblingWindow<I::Item> for I
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    #[inline]
+    fn tumbling_window<Lhs>(self, size: SpanOf<I::Item>) -> TumblingWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Accumulable<I::Item>,
+    {
+        TumblingWindows::new(self, size)
+    }
+}
+
+pub(crate) struct Identity<Lhs> {
+    value: Lhs,
+    clone: fn(&Lhs) -> Lhs,
+}
+
+impl<Lhs> Identity<Lhs> {
+    pub fn new(value: Lhs) -> Self
+    where
+        Lhs: Clone,
+    {
+        Self {
+            value,
+            clone: Lhs::clone,
+        }
+    }
+
+    pub fn get(&self) -> Lhs {
+        (self.clone)(&self.value)
+    }
+}
+
+/// Folds items into fixed windows aligned to multiples of `size`, yielding
+/// `(window_start, Lhs)`.
+///
+/// Items are expected in timestamp order; an item earlier than the open window
+/// is accumulated into it.
+pub struct TumblingWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    iter: I,
+    size: SpanOf<I::Item>,
+    open: Option<(TimestampOf<I::Item>, Lhs)>,
+    empty: Option<Range<TimestampOf<I::Item>>>,
+    identity: Option<Identity<Lhs>>,
+}
+
+impl<I, Lhs> TumblingWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    pub fn new(iter: I, size: SpanOf<I::Item>) -> Self {
+        assert!(size > Default::default(), "window size must be positive");
+
+        Self {
+            iter,
+            size,
+            open: None,
+            empty: None,
+            identity: None,
+        }
+    }
+
+    /// Yields `identity` for every window without items between two non-empty ones.
+    pub fn fill_empty(mut self, identity: Lhs) -> Self
+    where
+        Lhs: Clone,
+    {
+        self.identity = Some(Identity::new(identity));
+
+        self
+    }
+}
+
+impl<I, Lhs> Iterator for TumblingWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+{
+    type Item = (TimestampOf<I::Item>, Lhs);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let (Some(empty), Some(identity)) = (&mut self.empty, &self.identity) {
+            if empty.start < empty.end {
+                let start = empty.start;
+
+                empty.start = start.add_span(self.size);
+
+                return Some((start, identity.get()));
+            }
+
+            self.empty = None;
+        }
+
+        for rhs in self.iter.by_ref() {
+            let start = rhs.timestamp().floor(self.size);
+
+            match &mut self.open {
+                Some((open, lhs)) if start <= *open => lhs.accumulate_from(&rhs),
+                _ => {
+                    if let Some((previous, lhs)) = self.open.replace((start, Lhs::from(rhs))) {
+                        self.empty = Some(previous.add_span(self.size)..start);
+
+                        return Some((previous, lhs));
+                    }
+                }
+            }
+        }
+
+        self.open.take()
+    }
+}
+
+pub trait SessionWindow<Rhs>: Iterator<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn session_windows<Lhs>(self, gap: SpanOf<Rhs>) -> SessionWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<I> SessionWindow<I::Item> for I
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    #[inline]
+    fn session_windows<Lhs>(self, gap: SpanOf<I::Item>) -> SessionWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Accumulable<I::Item>,
+    {
+        SessionWindows::new(self, gap)
+    }
+}
+
+pub(crate) struct Session<T, Lhs> {
+    pub start: T,
+    pub end: T,
+    pub lhs: Lhs,
+}
+
+impl<T, Lhs> Session<T, Lhs>
+where
+    T: Timestamp,
+{
+    pub fn new<Rhs>(rhs: Rhs) -> Self
+    where
+        Rhs: Timestamped<Timestamp = T>,
+        Lhs: From<Rhs>,
+    {
+        let timestamp = rhs.timestamp();
+
+        Self {
+            start: timestamp,
+            end: timestamp,
+            lhs: Lhs::from(rhs),
+        }
+    }
+
+    /// Accumulates `rhs` unless it is more than `gap` after the session's end.
+    pub fn maybe_accumulate_from<Rhs>(&mut self, rhs: &Rhs, gap: T::Span) -> bool
+    where
+        Rhs: Timestamped<Timestamp = T>,
+        Lhs: Accumulable<Rhs>,
+    {
+        let timestamp = rhs.timestamp();
+
+        if timestamp.since(self.end) > gap {
+            return false;
+        }
+
+        self.lhs.accumulate_from(rhs);
+        self.start = self.start.min(timestamp);
+        self.end = self.end.max(timestamp);
+
+        true
+    }
+
+    pub fn into_tuple(self) -> (T, T, Lhs) {
+        (self.start, self.end, self.lhs)
+    }
+}
+
+/// Groups items into sessions, yielding `(session_start, session_end, Lhs)`.
+///
+/// A session goes on while each item is at most `gap` after the latest
+/// timestamp seen in it; the end is the timestamp of that latest item.
+pub struct SessionWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    iter: I,
+    gap: SpanOf<I::Item>,
+    open: Option<Session<TimestampOf<I::Item>, Lhs>>,
+}
+
+impl<I, Lhs> SessionWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    pub fn new(iter: I, gap: SpanOf<I::Item>) -> Self {
+        assert!(gap > Default::default(), "session gap must be positive");
+
+        Self {
+            iter,
+            gap,
+            open: None,
+        }
+    }
+}
+
+impl<I, Lhs> Iterator for SessionWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+{
+    type Item = (TimestampOf<I::Item>, TimestampOf<I::Item>, Lhs);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for rhs in self.iter.by_ref() {
+            let accumulated = match &mut self.open {
+                Some(session) => session.maybe_accumulate_from(&rhs, self.gap),
+                None => false,
+            };
+
+            if !accumulated {
+                if let Some(session) = self.open.replace(Session::new(rhs)) {
+                    return Some(session.into_tuple());
+                }
+            }
+        }
+
+        self.open.take().map(Session::into_tuple)
+    }
+}
+
+pub trait SlidingTimeWindow<Rhs>: Iterator<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn sliding_time_window<Lhs>(
+        self,
+        size: SpanOf<Rhs>,
+        hop: SpanOf<Rhs>,
+    ) -> SlidingTimeWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs> + Associative + Clone;
+}
+
+impl<I> SlidingTimeWindow<I::Item> for I
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    #[inline]
+    fn sliding_time_window<Lhs>(
+        self,
+        size: SpanOf<I::Item>,
+        hop: SpanOf<I::Item>,
+    ) -> SlidingTimeWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<I::Item> + Accumulable<I::Item> + Associative + Clone,
+    {
+        SlidingTimeWindows::new(self, size, hop)
+    }
+}
+
+/// Windows of `size` starting every `hop`, yielding `(window_start, Lhs)` for
+/// every window holding at least one item.
+///
+/// Items are folded once into `hop` wide panes, which are then combined per
+/// window. Panes are never split, so a `size` that is not a multiple of `hop`
+/// is rounded up to the next one. Items are expected in timestamp order; an
+/// item earlier than the latest pane is accumulated into it.
+///
+/// Panics if `hop` is not positive or `size` is less than `hop`.
+pub struct SlidingTimeWindows<I, Lhs, C = Recombine>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    iter: I,
+    panes: Panes<I::Item, Lhs, C>,
+    flushed: bool,
+}
+
+impl<I, Lhs> SlidingTimeWindows<I, Lhs>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    pub fn new(iter: I, size: SpanOf<I::Item>, hop: SpanOf<I::Item>) -> Self {
+        Self {
+            iter,
+            panes: Panes::new(size, hop, Recombine),
+            flushed: false,
+        }
+    }
+}
+
+impl<I, Lhs, C> SlidingTimeWindows<I, Lhs, C>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+{
+    /// Maintains a running total with [`Deaccumulable`] instead of combining
+    /// all panes of every window.
+    pub fn deaccumulate(self) -> SlidingTimeWindows<I, Lhs, Deaccumulate<Lhs>>
+    where
+        Lhs: Commutative + Deaccumulable,
+    {
+        SlidingTimeWindows {
+            iter: self.iter,
+            panes: self.panes.with_combine(Deaccumulate::default()),
+            flushed: self.flushed,
+        }
+    }
+}
+
+impl<I, Lhs, C> Iterator for SlidingTimeWindows<I, Lhs, C>
+where
+    I: Iterator,
+    I::Item: Timestamped,
+    Lhs: From<I::Item> + Accumulable<I::Item>,
+    C: PaneCombine<Lhs>,
+{
+    type Item = (TimestampOf<I::Item>, Lhs);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(window) = self.panes.pop_ready() {
+                return Some(window);
+            }
+
+            if self.flushed {
+                return None;
+            }
+
+            match self.iter.next() {
+                Some(rhs) => self.panes.push(rhs),
+                None => {
+                    self.flushed = true;
+
+                    self.panes.flush();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    #[test]
+    fn test_accumulate_zero() {
+        let volumes = [];
+
+        assert_eq!(volumes.into_iter().accumulate::<Volume>(), None);
+    }
+
+    #[test]
+    fn test_accumulate_one() {
+        let volumes = [Volume(10)];
+
+        assert_eq!(
+            volumes.into_iter().accumulate::<Volume>().unwrap(),
+            Volume(10)
+        );
+    }
+
+    #[test]
+    fn test_accumulate() {
+        let volumes = [Volume(10), Volume(15), Volume(20), Volume(25), Volume(30)];
+
+        assert_eq!(
+            volumes.into_iter().accumulate::<Volume>().unwrap(),
+            Volume(100)
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum VolumeSize<const N: u64> {
+        Large(Volume),
+        Small(Volume),
+    }
+
+    impl<const N: u64> VolumeSize<N> {
+        pub fn new(volume: Volume) -> Self {
+            if volume.0 >= N {
+                Self::Large(volume)
+            } else {
+                Self::Small(volume)
+            }
+        }
+
+        pub fn volume_value(&self) -> Volume {
+            match self {
+                Self::Large(x) | Self::Small(x) => *x,
+            }
+        }
+    }
+
+    impl<const N: u64> Accumulable for VolumeSize<N> {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = VolumeSize::new(self.volume_value().accumulate(&rhs.volume_value()))
+        }
+    }
+
+    impl<const N: u64> MaybeAccumulable for VolumeSize<N> {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            match self {
+                VolumeSize::Small(_) => {
+                    self.accumulate_from(rhs);
+
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Price(f64);
+
+    impl Accumulable for Price {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 += rhs.0;
+        }
+    }
+
+    impl Associative for Price {}
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Tape(Vec<u32>);
+
+    impl From<u32> for Tape {
+        fn from(value: u32) -> Self {
+            Tape(vec![value])
+        }
+    }
+
+    impl Accumulable for Tape {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0.extend_from_slice(&rhs.0);
+        }
+    }
+
+    impl Associative for Tape {}
+
+    #[test]
+    fn tree_accumulate_order() {
+        let tree = (0..1000u32).tree_accumulate::<Tape>();
+
+        assert_eq!(tree, Some(Tape((0..1000).collect())));
+        assert_eq!(std::iter::empty::<u32>().tree_accumulate::<Tape>(), None);
+    }
+
+    #[test]
+    fn tree_accumulate_precision() {
+        let prices = || std::iter::repeat_n(Price(0.1), 1_000_000);
+
+        let sequential = prices().accumulate::<Price>().unwrap();
+        let tree = prices().tree_accumulate::<Price>().unwrap();
+
+        assert!((sequential.0 - 100_000.0).abs() > 1e-7);
+        assert!((tree.0 - 100_000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn partially_accumulate_zero() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [];
+
+        let partially_accumulated = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .collect::<Vec<_>>();
+
+        assert_eq!(partially_accumulated, vec![])
+    }
+
+    #[test]
+    fn partially_accumulate_one() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [VolumeSize100::new(Volume(60))];
+
+        let partially_accumulated = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .collect::<Vec<_>>();
+
+        assert_eq!(partially_accumulated, vec![VolumeSize100::Small(Volume(60))])
+    }
+
+    #[test]
+    fn partially_accumulate() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [
+            VolumeSize100::new(Volume(60)),
+            VolumeSize100::new(Volume(30)),
+            VolumeSize100::new(Volume(15)),
+            //
+            VolumeSize100::new(Volume(40)),
+            VolumeSize100::new(Volume(70)),
+            //
+            VolumeSize100::new(Volume(80)),
+            VolumeSize100::new(Volume(10)),
+            VolumeSize100::new(Volume(5)),
+            VolumeSize100::new(Volume(3)),
+            VolumeSize100::new(Volume(2)),
+            //
+            VolumeSize100::new(Volume(1)),
+            VolumeSize100::new(Volume(1)),
+        ];
+
+        let partially_accumulated = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            partially_accumulated,
+            vec![
+                VolumeSize100::Large(Volume(105)),
+                VolumeSize100::Large(Volume(110)),
+                VolumeSize100::Large(Volume(100)),
+                VolumeSize100::Small(Volume(2))
+            ]
+        )
+    }
+
+    #[test]
+    fn partially_accumulate_max_items() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [1, 1, 1, 1, 1, 99, 1].map(|volume| VolumeSize100::new(Volume(volume)));
+
+        let max_items = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .max_items(2)
+            .collect::<Vec<_>>();
+        let max_merges = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .max_merges(2)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            max_items,
+            vec![
+                VolumeSize100::Small(Volume(2)),
+                VolumeSize100::Small(Volume(2)),
+                VolumeSize100::Large(Volume(100)),
+                VolumeSize100::Small(Volume(1))
+            ]
+        );
+        assert_eq!(
+            max_merges,
+            vec![
+                VolumeSize100::Small(Volume(3)),
+                VolumeSize100::Large(Volume(101)),
+                VolumeSize100::Small(Volume(1))
+            ]
+        );
+    }
+
+    #[test]
+    fn partially_accumulate_rev() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1]
+            .map(|volume| VolumeSize100::new(Volume(volume)));
+
+        let partially_accumulated = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .rev()
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            partially_accumulated,
+            vec![
+                VolumeSize100::Large(Volume(102)),
+                VolumeSize100::Large(Volume(110)),
+                VolumeSize100::Large(Volume(105))
+            ]
+        )
+    }
+
+    #[test]
+    fn partially_accumulate_meet_in_the_middle() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1]
+            .map(|volume| VolumeSize100::new(Volume(volume)));
+
+        let mut partially_accumulated = volumes.into_iter().partially_accumulate::<VolumeSize100>();
+
+        assert_eq!(
+            partially_accumulated.next(),
+            Some(VolumeSize100::Large(Volume(105)))
+        );
+        assert_eq!(
+            partially_accumulated.next_back(),
+            Some(VolumeSize100::Large(Volume(102)))
+        );
+        assert_eq!(
+            partially_accumulated.next(),
+            Some(VolumeSize100::Small(Volume(40)))
+        );
+        assert_eq!(
+            partially_accumulated.next(),
+            Some(VolumeSize100::Small(Volume(70)))
+        );
+        assert_eq!(partially_accumulated.next_back(), None);
+        assert_eq!(partially_accumulated.next(), None);
+    }
+
+    #[test]
+    fn partially_accumulate_mixed_directions() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1]
+            .map(|volume| VolumeSize100::new(Volume(volume)));
+
+        let forward = volumes
+            .into_iter()
+            .partially_accumulate::<VolumeSize100>()
+            .collect::<Vec<_>>();
+
+        let mut partially_accumulated = volumes.into_iter().partially_accumulate::<VolumeSize100>();
+
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+
+        while let Some(lhs) = partially_accumulated.next() {
+            front.push(lhs);
+
+            match partially_accumulated.next_back() {
+                Some(lhs) => back.push(lhs),
+                None => break,
+            }
+        }
+
+        front.extend(back.into_iter().rev());
+
+        assert_eq!(
+            forward,
+            vec![
                 VolumeSize100::Large(Volume(105)),
                 VolumeSize100::Large(Volume(110)),
                 VolumeSize100::Large(Volume(100)),
                 VolumeSize100::Small(Volume(2))
             ]
+        );
+
+        // The run `40, 70` crosses the point where the ends meet.
+        assert_eq!(
+            front,
+            vec![
+                VolumeSize100::Large(Volume(105)),
+                VolumeSize100::Small(Volume(40)),
+                VolumeSize100::Small(Volume(70)),
+                VolumeSize100::Large(Volume(102))
+            ]
+        );
+    }
+
+    impl MaybeAccumulableLookahead for Volume {
+        fn maybe_accumulate_from_lookahead(&mut self, rhs: &Self, lookahead: &[Self]) -> bool {
+            if lookahead.iter().any(|volume| volume.0 >= 100) {
+                return false;
+            }
+
+            self.accumulate_from(rhs);
+
+            true
+        }
+    }
+
+    #[test]
+    fn partially_accumulate_lookahead() {
+        let volumes = [10, 20, 30, 150, 5, 5].map(Volume);
+
+        let one = volumes
+            .into_iter()
+            .partially_accumulate_lookahead::<Volume>(1)
+            .collect::<Vec<_>>();
+        let two = volumes
+            .into_iter()
+            .partially_accumulate_lookahead::<Volume>(2)
+            .collect::<Vec<_>>();
+
+        assert_eq!(one, [30, 190].map(Volume).to_vec());
+        assert_eq!(two, [10, 20, 190].map(Volume).to_vec());
+    }
+
+    impl Weighable for Volume {
+        fn weight(&self) -> usize {
+            self.0 as usize
+        }
+    }
+
+    #[test]
+    fn accumulate_up_to_weight() {
+        let volumes = [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1, 150, 20].map(Volume);
+
+        let batches = volumes
+            .into_iter()
+            .accumulate_up_to_weight::<Volume>(100)
+            .collect::<Vec<_>>();
+
+        assert_eq!(batches, [90, 55, 70, 100, 2, 150, 20].map(Volume).to_vec())
+    }
+
+    #[test]
+    fn partially_accumulate_max_items_boundary() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [1, 1, 1, 1, 1, 1, 1].map(|volume| VolumeSize100::new(Volume(volume)));
+
+        for (max_items, expected) in [(1, vec![1; 7]), (3, vec![3, 3, 1]), (7, vec![7])] {
+            let groups = volumes
+                .into_iter()
+                .partially_accumulate::<VolumeSize100>()
+                .max_items(max_items)
+                .map(|lhs| lhs.volume_value().0)
+                .collect::<Vec<_>>();
+
+            assert_eq!(groups, expected);
+        }
+    }
+
+    #[test]
+    fn accumulate_up_to_weight_max_items_boundary() {
+        let volumes = [1, 1, 1, 1, 1, 1, 1].map(Volume);
+
+        for (max_items, expected) in [(1, vec![1; 7]), (3, vec![3, 3, 1]), (7, vec![7])] {
+            let groups = volumes
+                .into_iter()
+                .accumulate_up_to_weight::<Volume>(100)
+                .max_items(max_items)
+                .map(|lhs| lhs.0)
+                .collect::<Vec<_>>();
+
+            assert_eq!(groups, expected);
+        }
+    }
+
+    #[test]
+    fn accumulate_up_to_weight_max_items() {
+        let volumes = [10, 20, 30, 40, 50].map(Volume);
+
+        let batches = volumes
+            .into_iter()
+            .accumulate_up_to_weight::<Volume>(100)
+            .max_items(2)
+            .collect::<Vec<_>>();
+
+        assert_eq!(batches, [30, 70, 50].map(Volume).to_vec())
+    }
+
+    impl Splittable for Volume {
+        fn split_at(self, amount: usize) -> (Self, Self) {
+            (Volume(amount as u64), Volume(self.0 - amount as u64))
+        }
+    }
+
+    #[test]
+    fn partially_accumulate_exact() {
+        let volumes = [60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1].map(Volume);
+
+        let bars = volumes
+            .into_iter()
+            .partially_accumulate_exact::<Volume>(100)
+            .collect::<Vec<_>>();
+
+        assert_eq!(bars, [100, 100, 100, 17].map(Volume).to_vec())
+    }
+
+    #[test]
+    fn partially_accumulate_exact_spanning() {
+        let volumes = [250, 10, 40].map(Volume);
+
+        let bars = volumes
+            .into_iter()
+            .partially_accumulate_exact::<Volume>(100)
+            .collect::<Vec<_>>();
+
+        assert_eq!(bars, [100, 100, 100].map(Volume).to_vec())
+    }
+
+    #[test]
+    fn bin_pack() {
+        let volumes = [50, 70, 20, 150, 30].map(Volume);
+
+        let first = volumes
+            .into_iter()
+            .bin_pack::<Volume>(100, Fit::First)
+            .collect::<Vec<_>>();
+        let best = volumes
+            .into_iter()
+            .bin_pack::<Volume>(100, Fit::Best)
+            .collect::<Vec<_>>();
+
+        assert_eq!(first, [150, 100, 70].map(Volume).to_vec());
+        assert_eq!(best, [150, 80, 90].map(Volume).to_vec());
+    }
+
+    #[test]
+    fn bin_pack_decreasing() {
+        let volumes = [10, 60, 50, 40, 40].map(Volume);
+
+        let packed = volumes
+            .into_iter()
+            .bin_pack::<Volume>(100, Fit::First)
+            .collect::<Vec<_>>();
+        let decreasing = volumes
+            .into_iter()
+            .bin_pack::<Volume>(100, Fit::First)
+            .decreasing()
+            .collect::<Vec<_>>();
+
+        assert_eq!(packed, [70, 90, 40].map(Volume).to_vec());
+        assert_eq!(decreasing, [100, 100].map(Volume).to_vec());
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Trade {
+        time: u64,
+        volume: Volume,
+    }
+
+    impl Timestamped for Trade {
+        type Timestamp = u64;
+
+        fn timestamp(&self) -> u64 {
+            self.time
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct TradeVolume(u64);
+
+    impl From<Trade> for TradeVolume {
+        fn from(trade: Trade) -> Self {
+            TradeVolume(trade.volume.0)
+        }
+    }
+
+    impl Accumulable<Trade> for TradeVolume {
+        fn accumulate_from(&mut self, rhs: &Trade) {
+            self.0 += rhs.volume.0;
+        }
+    }
+
+    impl Accumulable for TradeVolume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 += rhs.0;
+        }
+    }
+
+    impl Deaccumulable for TradeVolume {
+        fn deaccumulate_from(&mut self, rhs: &Self) {
+            self.0 -= rhs.0;
+        }
+    }
+
+    impl Associative for TradeVolume {}
+
+    impl Commutative for TradeVolume {}
+
+    fn trade(time: u64, volume: u64) -> Trade {
+        Trade {
+            time,
+            volume: Volume(volume),
+        }
+    }
+
+    #[test]
+    fn tumbling_window() {
+        let trades = [
+            trade(0, 10),
+            trade(10, 15),
+            trade(59, 20),
+            //
+            trade(60, 25),
+            //
+            trade(125, 30),
+            trade(130, 5),
+        ];
+
+        let windows = trades
+            .into_iter()
+            .tumbling_window::<TradeVolume>(60)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            windows,
+            vec![
+                (0, TradeVolume(45)),
+                (60, TradeVolume(25)),
+                (120, TradeVolume(35))
+            ]
+        )
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be positive")]
+    fn tumbling_window_zero_size() {
+        [trade(0, 10)]
+            .into_iter()
+            .tumbling_window::<TradeVolume>(0)
+            .for_each(drop);
+    }
+
+    #[test]
+    fn tumbling_window_fill_empty() {
+        let trades = [trade(30, 10), trade(200, 15)];
+
+        let windows = trades
+            .into_iter()
+            .tumbling_window::<TradeVolume>(60)
+            .fill_empty(TradeVolume(0))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            windows,
+            vec![
+                (0, TradeVolume(10)),
+                (60, TradeVolume(0)),
+                (120, TradeVolume(0)),
+                (180, TradeVolume(15))
+            ]
+        )
+    }
+
+    #[test]
+    fn session_windows() {
+        let trades = [
+            trade(0, 10),
+            trade(5, 15),
+            trade(15, 20),
+            //
+            trade(30, 25),
+            trade(28, 5),
+            //
+            trade(50, 30),
+        ];
+
+        let sessions = trades
+            .into_iter()
+            .session_windows::<TradeVolume>(10)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            sessions,
+            vec![
+                (0, 15, TradeVolume(45)),
+                (28, 30, TradeVolume(30)),
+                (50, 50, TradeVolume(30))
+            ]
+        )
+    }
+
+    #[test]
+    #[should_panic(expected = "session gap must be positive")]
+    fn session_windows_zero_gap() {
+        [trade(0, 10)]
+            .into_iter()
+            .session_windows::<TradeVolume>(0)
+            .for_each(drop);
+    }
+
+    #[test]
+    fn sliding_time_window() {
+        let trades = [
+            trade(0, 1),
+            trade(5, 2),
+            trade(12, 3),
+            trade(25, 4),
+            trade(41, 5),
+        ];
+
+        let expected = vec![
+            (0, TradeVolume(10)),
+            (10, TradeVolume(7)),
+            (20, TradeVolume(9)),
+            (30, TradeVolume(5)),
+            (40, TradeVolume(5)),
+        ];
+
+        let recombined = trades
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(30, 10)
+            .collect::<Vec<_>>();
+
+        let deaccumulated = trades
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(30, 10)
+            .deaccumulate()
+            .collect::<Vec<_>>();
+
+        assert_eq!(recombined, expected);
+        assert_eq!(deaccumulated, expected);
+    }
+
+    #[test]
+    fn sliding_time_window_gap() {
+        let trades = [trade(3, 1), trade(105, 2)];
+
+        let windows = trades
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(20, 10)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            windows,
+            vec![
+                (0, TradeVolume(1)),
+                (90, TradeVolume(2)),
+                (100, TradeVolume(2))
+            ]
         )
     }
+
+    #[test]
+    fn sliding_time_window_rounds_size_up() {
+        let trades = [trade(3, 1), trade(12, 2), trade(27, 4)];
+
+        let rounded = trades
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(15, 10)
+            .collect::<Vec<_>>();
+
+        let multiple = trades
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(20, 10)
+            .collect::<Vec<_>>();
+
+        assert_eq!(rounded, multiple);
+    }
+
+    #[test]
+    #[should_panic(expected = "hop must be positive")]
+    fn sliding_time_window_zero_hop() {
+        [trade(0, 10)]
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(20, 0)
+            .for_each(drop);
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be at least hop")]
+    fn sliding_time_window_size_below_hop() {
+        [trade(0, 10)]
+            .into_iter()
+            .sliding_time_window::<TradeVolume>(5, 10)
+            .for_each(drop);
+    }
 }
@@ -0,0 +1,135 @@
+//! Checks that the accumulators shipped with the crate satisfy the algebraic
+//! properties their marker traits claim, up to rounding.
+
+use quickcheck::{Arbitrary, Gen};
+
+use crate::iter::Accumulate;
+use crate::sketch::TDigest;
+use crate::stats::Moments;
+use crate::sum::{KahanSum, NeumaierSum};
+use crate::testing::{check_commutative_laws, check_identity};
+use crate::{Accumulable, Associative, Commutative};
+
+/// Equality up to the accuracy an accumulator promises.
+trait Close {
+    fn close(&self, other: &Self) -> bool;
+}
+
+/// Values are generated small enough that rounding stays far below the
+/// tolerance, while a real mistake is off by at least one step of 0.1.
+fn close(a: f64, b: f64) -> bool {
+    a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
+
+impl Close for KahanSum<f64> {
+    fn close(&self, other: &Self) -> bool {
+        close(self.value(), other.value())
+    }
+}
+
+impl Close for NeumaierSum<f64> {
+    fn close(&self, other: &Self) -> bool {
+        close(self.value(), other.value())
+    }
+}
+
+impl Close for Moments {
+    fn close(&self, other: &Self) -> bool {
+        // Higher moments of nearly equal values are dominated by rounding.
+        let shaped = self.variance() > 1.0;
+
+        self.count() == other.count()
+            && close(self.mean(), other.mean())
+            && close(self.variance(), other.variance())
+            && (!shaped || close(self.skewness(), other.skewness()))
+            && (!shaped || close(self.kurtosis(), other.kurtosis()))
+    }
+}
+
+impl Close for TDigest {
+    fn close(&self, other: &Self) -> bool {
+        let range = self
+            .max()
+            .zip(self.min())
+            .map_or(0.0, |(max, min)| max - min);
+
+        self.count() == other.count()
+            && self.min() == other.min()
+            && self.max() == other.max()
+            && [0.1, 0.5, 0.9].into_iter().all(|q| {
+                let (a, b) = (self.quantile(q).unwrap(), other.quantile(q).unwrap());
+
+                (a - b).abs() <= 0.05 * range
+            })
+    }
+}
+
+/// An accumulator of a non-empty run of arbitrary values, compared with
+/// [`Close`].
+#[derive(Clone, Debug, Default)]
+struct Approx<T>(T);
+
+impl<T> PartialEq for Approx<T>
+where
+    T: Close,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0.close(&other.0)
+    }
+}
+
+impl<T> Arbitrary for Approx<T>
+where
+    T: From<f64> + Accumulable<f64> + Clone + 'static,
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        let first = i16::arbitrary(g);
+        let rest = Vec::<i16>::arbitrary(g);
+
+        let values = std::iter::once(first).chain(rest);
+
+        Approx(
+            values
+                .map(|value| f64::from(value) / 10.0)
+                .accumulate::<T>()
+                .unwrap(),
+        )
+    }
+}
+
+impl<T> Accumulable for Approx<T>
+where
+    T: Accumulable,
+{
+    fn accumulate_from(&mut self, rhs: &Self) {
+        self.0.accumulate_from(&rhs.0);
+    }
+}
+
+impl<T> Associative for Approx<T> where T: Associative {}
+
+impl<T> Commutative for Approx<T> where T: Commutative {}
+
+#[test]
+fn kahan_sum() {
+    check_commutative_laws::<Approx<KahanSum<f64>>>();
+    check_identity::<Approx<KahanSum<f64>>>();
+}
+
+#[test]
+fn neumaier_sum() {
+    check_commutative_laws::<Approx<NeumaierSum<f64>>>();
+    check_identity::<Approx<NeumaierSum<f64>>>();
+}
+
+#[test]
+fn moments() {
+    check_commutative_laws::<Approx<Moments>>();
+    check_identity::<Approx<Moments>>();
+}
+
+#[test]
+fn t_digest() {
+    check_commutative_laws::<Approx<TDigest>>();
+    check_identity::<Approx<TDigest>>();
+}
@@ -1,6 +1,21 @@
+pub mod checked;
+pub mod checkpoint;
+pub mod coalesce;
+pub mod conflate;
 pub mod iter;
+#[cfg(test)]
+mod laws;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod sketch;
+pub mod stats;
 pub mod stream;
+pub mod sum;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod time;
 pub mod try_stream;
+pub mod window;
 
 pub trait Accumulable<Rhs = Self> {
     fn accumulate_from(&mut self, rhs: &Rhs);
@@ -31,3 +46,52 @@ pub trait MaybeAccumulable<Rhs = Self> {
         }
     }
 }
+
+/// Marker for [`Accumulable`] impls where `(a + b) + c == a + (b + c)`, so runs
+/// of items can be accumulated separately and then merged in order.
+///
+/// Floating point accumulators, like those in [`sum`] and [`stats`], only
+/// satisfy this and [`Commutative`] up to rounding, and approximate ones, like
+/// those in [`sketch`], up to their accuracy: merging in a different order may
+/// change the result by that much.
+pub trait Associative: Accumulable + Sized {}
+
+/// Marker for [`Associative`] impls where `a + b == b + a`, so partial results
+/// can also be merged in any order.
+pub trait Commutative: Associative {}
+
+/// Like [`MaybeAccumulable`], but the decision may also depend on up to a
+/// bounded number of items following `rhs`.
+pub trait MaybeAccumulableLookahead<Rhs = Self> {
+    fn maybe_accumulate_from_lookahead(&mut self, rhs: &Rhs, lookahead: &[Rhs]) -> bool;
+}
+
+/// Inverse of [`Accumulable`]: removes a previously accumulated `rhs`.
+pub trait Deaccumulable<Rhs = Self> {
+    fn deaccumulate_from(&mut self, rhs: &Rhs);
+}
+
+/// Which open bin an item goes into when packing, see `bin_pack`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fit {
+    /// The earliest opened bin with enough room.
+    First,
+    /// The bin with the least room left that still fits.
+    Best,
+}
+
+pub trait Timestamped {
+    type Timestamp: time::Timestamp;
+
+    fn timestamp(&self) -> Self::Timestamp;
+}
+
+/// A size measure, e.g. bytes, used to bound groups by total weight.
+pub trait Weighable {
+    fn weight(&self) -> usize;
+}
+
+/// Splits off the first `amount` of weight, as measured by [`Weighable`].
+pub trait Splittable: Sized {
+    fn split_at(self, amount: usize) -> (Self, Self);
+}
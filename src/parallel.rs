@@ -0,0 +1,263 @@
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+
+use crate::{Accumulable, Associative, Commutative, MaybeAccumulable};
+
+pub trait ParallelAccumulate<Rhs>: ParallelIterator<Item = Rhs> {
+    /// Accumulates runs of items on separate threads and merges the partial
+    /// results in order; equal to the sequential
+    /// [`accumulate`](crate::iter::Accumulate::accumulate).
+    fn par_accumulate<Lhs>(self) -> Option<Lhs>
+    where
+        Self: IndexedParallelIterator,
+        Lhs: From<Rhs> + Accumulable<Rhs> + Associative + Send;
+
+    /// Like [`par_accumulate`](Self::par_accumulate), but for any parallel
+    /// iterator, as partial results may be merged in whatever order they finish.
+    fn par_accumulate_unordered<Lhs>(self) -> Option<Lhs>
+    where
+        Lhs: From<Rhs> + Accumulable<Rhs> + Commutative + Send;
+}
+
+impl<P> ParallelAccumulate<P::Item> for P
+where
+    P: ParallelIterator,
+{
+    #[inline]
+    fn par_accumulate<Lhs>(self) -> Option<Lhs>
+    where
+        Self: IndexedParallelIterator,
+        Lhs: From<P::Item> + Accumulable<P::Item> + Associative + Send,
+    {
+        accumulate(self)
+    }
+
+    #[inline]
+    fn par_accumulate_unordered<Lhs>(self) -> Option<Lhs>
+    where
+        Lhs: From<P::Item> + Accumulable<P::Item> + Commutative + Send,
+    {
+        accumulate(self)
+    }
+}
+
+pub trait ParallelPartiallyAccumulate<Rhs>: IndexedParallelIterator<Item = Rhs> {
+    /// Partially accumulates runs of items on separate threads, then stitches
+    /// each run's last group to the next run's first group with
+    /// `MaybeAccumulable<Lhs>`.
+    ///
+    /// The result equals the sequential
+    /// [`partially_accumulate`](crate::iter::PartiallyAccumulate::partially_accumulate)
+    /// when, for any group `a` followed by a group `b`:
+    ///
+    /// - merging `b` into `a` succeeds exactly when `a` would have absorbed all
+    ///   of `b`'s items one by one, and yields the same value;
+    /// - an item rejected by a group is also rejected by that group once it
+    ///   has been extended with earlier items, so stitching never reopens a
+    ///   boundary inside a run.
+    ///
+    /// Key-based merges, e.g. "same session id", satisfy both.
+    fn par_partially_accumulate<Lhs>(self) -> Vec<Lhs>
+    where
+        Lhs: From<Rhs> + MaybeAccumulable<Rhs> + MaybeAccumulable<Lhs> + Send;
+}
+
+impl<P> ParallelPartiallyAccumulate<P::Item> for P
+where
+    P: IndexedParallelIterator,
+{
+    fn par_partially_accumulate<Lhs>(self) -> Vec<Lhs>
+    where
+        Lhs: From<P::Item> + MaybeAccumulable<P::Item> + MaybeAccumulable<Lhs> + Send,
+    {
+        self.fold(Vec::new, |mut groups: Vec<Lhs>, rhs| {
+            let accumulated = groups
+                .last_mut()
+                .is_some_and(|lhs| lhs.maybe_accumulate_from(&rhs));
+
+            if !accumulated {
+                groups.push(Lhs::from(rhs));
+            }
+
+            groups
+        })
+        .reduce(Vec::new, |mut lhs, rhs| {
+            let mut rhs = rhs.into_iter();
+
+            if let Some(first) = rhs.next() {
+                let stitched = lhs
+                    .last_mut()
+                    .is_some_and(|last| last.maybe_accumulate_from(&first));
+
+                if !stitched {
+                    lhs.push(first);
+                }
+            }
+
+            lhs.extend(rhs);
+
+            lhs
+        })
+    }
+}
+
+fn accumulate<P, Lhs>(iter: P) -> Option<Lhs>
+where
+    P: ParallelIterator,
+    Lhs: From<P::Item> + Accumulable<P::Item> + Accumulable + Send,
+{
+    iter.fold(
+        || None,
+        |lhs: Option<Lhs>, rhs| match lhs {
+            Some(lhs) => Some(lhs.accumulate(&rhs)),
+            None => Some(Lhs::from(rhs)),
+        },
+    )
+    .reduce(
+        || None,
+        |lhs, rhs| match (lhs, rhs) {
+            (Some(lhs), Some(rhs)) => Some(lhs.accumulate(&rhs)),
+            (lhs, None) => lhs,
+            (None, rhs) => rhs,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelBridge};
+
+    use crate::iter::{Accumulate, PartiallyAccumulated};
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    impl Associative for Volume {}
+
+    impl Commutative for Volume {}
+
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct Tape(String);
+
+    impl From<u32> for Tape {
+        fn from(rhs: u32) -> Self {
+            Tape(rhs.to_string())
+        }
+    }
+
+    impl Accumulable<u32> for Tape {
+        fn accumulate_from(&mut self, rhs: &u32) {
+            self.0.push(',');
+            self.0.push_str(&rhs.to_string());
+        }
+    }
+
+    impl Accumulable for Tape {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0.push(',');
+            self.0.push_str(&rhs.0);
+        }
+    }
+
+    impl Associative for Tape {}
+
+    #[test]
+    fn par_accumulate_zero() {
+        assert_eq!((0..0u32).into_par_iter().par_accumulate::<Tape>(), None);
+    }
+
+    #[test]
+    fn par_accumulate_ordered() {
+        let parallel = (0..10_000u32).into_par_iter().par_accumulate::<Tape>();
+        let sequential = (0..10_000u32).accumulate::<Tape>();
+
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn par_accumulate_unordered() {
+        let parallel = (0..100_000)
+            .map(Volume)
+            .par_bridge()
+            .par_accumulate_unordered::<Volume>();
+
+        assert_eq!(parallel, Some(Volume(4_999_950_000)));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Run {
+        key: u8,
+        len: usize,
+    }
+
+    impl From<u8> for Run {
+        fn from(key: u8) -> Self {
+            Run { key, len: 1 }
+        }
+    }
+
+    impl MaybeAccumulable<u8> for Run {
+        fn maybe_accumulate_from(&mut self, rhs: &u8) -> bool {
+            self.maybe_accumulate_from(&Run::from(*rhs))
+        }
+    }
+
+    impl MaybeAccumulable for Run {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            if self.key != rhs.key {
+                return false;
+            }
+
+            self.len += rhs.len;
+
+            true
+        }
+    }
+
+    #[test]
+    fn par_partially_accumulate() {
+        let keys = [1, 1, 2, 2, 2, 1, 3, 3];
+
+        let runs = keys
+            .par_iter()
+            .copied()
+            .with_max_len(1)
+            .par_partially_accumulate::<Run>();
+
+        assert_eq!(
+            runs,
+            vec![
+                Run { key: 1, len: 2 },
+                Run { key: 2, len: 3 },
+                Run { key: 1, len: 1 },
+                Run { key: 3, len: 2 }
+            ]
+        );
+    }
+
+    #[test]
+    fn par_partially_accumulate_matches_sequential() {
+        fn prop(keys: Vec<u8>, max_len: u8) -> bool {
+            let keys = keys.into_iter().map(|key| key % 3).collect::<Vec<_>>();
+
+            let parallel = keys
+                .par_iter()
+                .copied()
+                .with_max_len(usize::from(max_len) + 1)
+                .par_partially_accumulate::<Run>();
+            let sequential =
+                PartiallyAccumulated::<_, Run>::new(keys.into_iter()).collect::<Vec<_>>();
+
+            parallel == sequential
+        }
+
+        quickcheck::quickcheck(prop as fn(Vec<u8>, u8) -> bool);
+    }
+}
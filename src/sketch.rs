@@ -0,0 +1,429 @@
+//! Mergeable sketches answering approximate queries in bounded memory.
+
+use std::f64::consts::PI;
+
+use crate::{Accumulable, Associative, Commutative};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// A merging t-digest: values are clustered into at most about `COMPRESSION`
+/// centroids, small near the tails, so extreme quantiles stay accurate while
+/// memory stays bounded.
+///
+/// Values are buffered and folded in once the buffer fills up. Merging two
+/// digests is associative and commutative up to the digest's accuracy, as
+/// [`Associative`] allows for approximate accumulators.
+///
+/// `COMPRESSION` must be positive:
+///
+/// ```compile_fail
+/// let digest = accumulable::sketch::TDigest::<0>::new();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct TDigest<const COMPRESSION: usize = 100> {
+    centroids: Vec<Centroid>,
+    buffer: Vec<Centroid>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl<const COMPRESSION: usize> Default for TDigest<COMPRESSION> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const COMPRESSION: usize> TDigest<COMPRESSION> {
+    const BUFFER: usize = 5 * COMPRESSION;
+
+    pub fn new() -> Self {
+        const { assert!(COMPRESSION > 0, "COMPRESSION must be positive") }
+
+        Self {
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// The estimated value below which a fraction `q` of the values lie, or
+    /// `None` when empty.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        let centroids = self.merged();
+
+        let (first, last) = centroids.first().zip(centroids.last())?;
+
+        if q <= 0.0 {
+            return Some(self.min);
+        }
+
+        if q >= 1.0 {
+            return Some(self.max);
+        }
+
+        let index = q * self.count as f64;
+
+        if index < first.weight / 2.0 {
+            return Some(interpolate(
+                self.min,
+                first.mean,
+                index / (first.weight / 2.0),
+            ));
+        }
+
+        let mut center = first.weight / 2.0;
+
+        for pair in centroids.windows(2) {
+            let next = center + (pair[0].weight + pair[1].weight) / 2.0;
+
+            if index < next {
+                return Some(interpolate(
+                    pair[0].mean,
+                    pair[1].mean,
+                    (index - center) / (next - center),
+                ));
+            }
+
+            center = next;
+        }
+
+        let tail = (index - center) / (last.weight / 2.0);
+
+        Some(interpolate(last.mean, self.max, tail.min(1.0)))
+    }
+
+    /// The estimated fraction of values at or below `x`, or `None` when empty.
+    pub fn rank(&self, x: f64) -> Option<f64> {
+        let centroids = self.merged();
+
+        let (first, last) = centroids.first().zip(centroids.last())?;
+
+        if x < self.min {
+            return Some(0.0);
+        }
+
+        if x >= self.max {
+            return Some(1.0);
+        }
+
+        let total = self.count as f64;
+
+        if x < first.mean {
+            return Some(
+                fraction(x - self.min, first.mean - self.min) * first.weight / 2.0 / total,
+            );
+        }
+
+        let mut center = first.weight / 2.0;
+
+        for pair in centroids.windows(2) {
+            let next = center + (pair[0].weight + pair[1].weight) / 2.0;
+
+            if x < pair[1].mean {
+                let index = center
+                    + fraction(x - pair[0].mean, pair[1].mean - pair[0].mean) * (next - center);
+
+                return Some(index / total);
+            }
+
+            center = next;
+        }
+
+        let index = center + fraction(x - last.mean, self.max - last.mean) * last.weight / 2.0;
+
+        Some(index / total)
+    }
+
+    fn merged(&self) -> Vec<Centroid> {
+        if self.buffer.is_empty() {
+            return self.centroids.clone();
+        }
+
+        let mut merged = self.clone();
+
+        merged.compress();
+
+        merged.centroids
+    }
+
+    fn push(&mut self, centroid: Centroid) {
+        self.buffer.push(centroid);
+
+        if self.buffer.len() >= Self::BUFFER {
+            self.compress();
+        }
+    }
+
+    /// Merges buffered values into the centroids, bounding each centroid's
+    /// weight with the `k1` scale function `k(q) = δ / 2π · asin(2q - 1)`.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+
+        let mut all = std::mem::take(&mut self.centroids);
+
+        all.append(&mut self.buffer);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total = all.iter().map(|centroid| centroid.weight).sum::<f64>();
+        let compression = COMPRESSION as f64;
+
+        let k = |q: f64| compression / (2.0 * PI) * (2.0 * q - 1.0).asin();
+        let k_inv = |k: f64| ((2.0 * PI * k / compression).sin() + 1.0) / 2.0;
+        let limit = |before: f64| k_inv((k(before / total) + 1.0).min(compression / 4.0));
+
+        let mut all = all.into_iter();
+        let mut current = all.next().unwrap();
+        let mut before = 0.0;
+        let mut q_limit = limit(before);
+
+        for next in all {
+            if (before + current.weight + next.weight) / total <= q_limit {
+                let weight = current.weight + next.weight;
+
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                q_limit = limit(before);
+
+                self.centroids.push(std::mem::replace(&mut current, next));
+            }
+        }
+
+        self.centroids.push(current);
+    }
+}
+
+fn interpolate(from: f64, to: f64, t: f64) -> f64 {
+    from + (to - from) * t
+}
+
+fn fraction(part: f64, whole: f64) -> f64 {
+    if whole > 0.0 {
+        part / whole
+    } else {
+        0.0
+    }
+}
+
+impl<const COMPRESSION: usize> From<f64> for TDigest<COMPRESSION> {
+    fn from(value: f64) -> Self {
+        Self::new().accumulate(&value)
+    }
+}
+
+impl<const COMPRESSION: usize> Accumulable<f64> for TDigest<COMPRESSION> {
+    fn accumulate_from(&mut self, rhs: &f64) {
+        self.count += 1;
+        self.min = self.min.min(*rhs);
+        self.max = self.max.max(*rhs);
+
+        self.push(Centroid {
+            mean: *rhs,
+            weight: 1.0,
+        });
+    }
+}
+
+impl<const COMPRESSION: usize> Accumulable for TDigest<COMPRESSION> {
+    fn accumulate_from(&mut self, rhs: &Self) {
+        self.count += rhs.count;
+        self.min = self.min.min(rhs.min);
+        self.max = self.max.max(rhs.max);
+
+        for centroid in rhs.centroids.iter().chain(&rhs.buffer) {
+            self.push(*centroid);
+        }
+    }
+}
+
+impl<const COMPRESSION: usize> Associative for TDigest<COMPRESSION> {}
+
+impl<const COMPRESSION: usize> Commutative for TDigest<COMPRESSION> {}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+
+    use crate::iter::{Accumulate, TumblingWindow};
+    use crate::stream::Accumulate as _;
+    use crate::Timestamped;
+
+    use super::*;
+
+    const N: u64 = 100_000;
+
+    /// `0..N` in a scrambled but deterministic order.
+    fn values() -> impl Iterator<Item = f64> {
+        (0..N).map(|i| (i * 7919 % N) as f64)
+    }
+
+    fn assert_quantiles<const C: usize>(digest: &TDigest<C>, tolerance: f64) {
+        for q in [0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999] {
+            let estimate = digest.quantile(q).unwrap() / N as f64;
+
+            assert!(
+                (estimate - q).abs() < tolerance,
+                "quantile({q}) was off by {}",
+                (estimate - q).abs()
+            );
+        }
+    }
+
+    #[test]
+    fn quantile() {
+        let digest = values().accumulate::<TDigest>().unwrap();
+
+        assert_eq!(digest.count(), N);
+        assert_eq!(digest.quantile(0.0), Some(0.0));
+        assert_eq!(digest.quantile(1.0), Some((N - 1) as f64));
+
+        assert_quantiles(&digest, 0.005);
+    }
+
+    #[test]
+    fn quantile_tails() {
+        let digest = values().accumulate::<TDigest>().unwrap();
+
+        for q in [0.0001, 0.9999] {
+            let estimate = digest.quantile(q).unwrap() / N as f64;
+
+            assert!((estimate - q).abs() < 0.0002);
+        }
+    }
+
+    #[test]
+    fn rank() {
+        let digest = values().accumulate::<TDigest>().unwrap();
+
+        assert_eq!(digest.rank(-1.0), Some(0.0));
+        assert_eq!(digest.rank(N as f64), Some(1.0));
+
+        for q in [0.001, 0.01, 0.5, 0.99, 0.999] {
+            let rank = digest.rank(q * N as f64).unwrap();
+
+            assert!((rank - q).abs() < 0.005, "rank at {q} was {rank}");
+        }
+    }
+
+    #[test]
+    fn compression() {
+        let coarse = values().accumulate::<TDigest<20>>().unwrap();
+        let fine = values().accumulate::<TDigest<200>>().unwrap();
+
+        assert!(coarse.merged().len() <= 20);
+        assert!(fine.merged().len() <= 200);
+        assert!(coarse.merged().len() < fine.merged().len());
+
+        assert_quantiles(&coarse, 0.05);
+        assert_quantiles(&fine, 0.002);
+    }
+
+    #[test]
+    fn merge() {
+        let values = values().collect::<Vec<_>>();
+
+        let merged = values
+            .chunks(N as usize / 10)
+            .map(|chunk| chunk.iter().copied().accumulate::<TDigest>().unwrap())
+            .accumulate::<TDigest>()
+            .unwrap();
+
+        assert_eq!(merged.count(), N);
+        assert_quantiles(&merged, 0.005);
+    }
+
+    #[test]
+    fn empty() {
+        let digest = TDigest::<100>::new();
+
+        assert_eq!(digest.count(), 0);
+        assert_eq!(digest.quantile(0.5), None);
+        assert_eq!(digest.rank(0.0), None);
+        assert_eq!(digest.min(), None);
+        assert_eq!(digest.max(), None);
+    }
+
+    #[tokio::test]
+    async fn stream_accumulate() {
+        let digest = stream::iter(values())
+            .accumulate::<TDigest>()
+            .await
+            .unwrap();
+
+        assert!((digest.quantile(0.5).unwrap() / N as f64 - 0.5).abs() < 0.005);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Latency {
+        time: u64,
+        millis: f64,
+    }
+
+    impl Timestamped for Latency {
+        type Timestamp = u64;
+
+        fn timestamp(&self) -> u64 {
+            self.time
+        }
+    }
+
+    impl From<Latency> for TDigest {
+        fn from(latency: Latency) -> Self {
+            TDigest::from(latency.millis)
+        }
+    }
+
+    impl Accumulable<Latency> for TDigest {
+        fn accumulate_from(&mut self, rhs: &Latency) {
+            self.accumulate_from(&rhs.millis);
+        }
+    }
+
+    #[test]
+    fn tumbling_window() {
+        let latencies = (0..20_000).map(|time| Latency {
+            time,
+            millis: (time * 7919 % 10_000) as f64 + if time < 10_000 { 0.0 } else { 10_000.0 },
+        });
+
+        let windows = latencies
+            .tumbling_window::<TDigest>(10_000)
+            .map(|(start, digest)| {
+                let median = digest.quantile(0.5).unwrap();
+                let p99 = digest.quantile(0.99).unwrap();
+
+                (start, median, p99)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(windows.len(), 2);
+
+        for ((start, median, p99), offset) in windows.into_iter().zip([0.0, 10_000.0]) {
+            assert_eq!(start, offset as u64);
+            assert!((median - offset - 5_000.0).abs() < 50.0);
+            assert!((p99 - offset - 9_900.0).abs() < 10.0);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Streaming statistics computed in one pass and merged across partitions.
+
+use crate::{Accumulable, Associative, Commutative};
+
+/// Count, mean and central moments up to the fourth, updated one value at a
+/// time (Welford) or merged pairwise (Chan et al., extended by Pébay).
+///
+/// Statistics of fewer values than they need are `NaN`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Moments {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    m3: f64,
+    m4: f64,
+}
+
+impl Moments {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// `NaN` when empty.
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.mean
+        }
+    }
+
+    /// Population variance, `NaN` when empty.
+    pub fn variance(&self) -> f64 {
+        if self.count == 0 {
+            f64::NAN
+        } else {
+            self.m2 / self.count as f64
+        }
+    }
+
+    /// Unbiased sample variance, `NaN` for fewer than two values.
+    pub fn sample_variance(&self) -> f64 {
+        if self.count < 2 {
+            f64::NAN
+        } else {
+            self.m2 / (self.count - 1) as f64
+        }
+    }
+
+    /// Population standard deviation, `NaN` when empty.
+    pub fn std_dev(&self) -> f64 {
+        self.variance().sqrt()
+    }
+
+    /// `NaN` when empty or when all values are equal.
+    pub fn skewness(&self) -> f64 {
+        if self.m2 == 0.0 {
+            f64::NAN
+        } else {
+            (self.count as f64).sqrt() * self.m3 / self.m2.powf(1.5)
+        }
+    }
+
+    /// Kurtosis in excess of the normal distribution's 3, `NaN` when empty or
+    /// when all values are equal.
+    pub fn kurtosis(&self) -> f64 {
+        if self.m2 == 0.0 {
+            f64::NAN
+        } else {
+            self.count as f64 * self.m4 / (self.m2 * self.m2) - 3.0
+        }
+    }
+}
+
+impl From<f64> for Moments {
+    fn from(value: f64) -> Self {
+        Self {
+            count: 1,
+            mean: value,
+            ..Self::default()
+        }
+    }
+}
+
+impl Accumulable<f64> for Moments {
+    fn accumulate_from(&mut self, rhs: &f64) {
+        let n1 = self.count as f64;
+
+        self.count += 1;
+
+        let n = self.count as f64;
+        let delta = rhs - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * n1;
+
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3.0 * n + 3.0) + 6.0 * delta_n2 * self.m2
+            - 4.0 * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.0) - 3.0 * delta_n * self.m2;
+        self.m2 += term;
+    }
+}
+
+impl Accumulable for Moments {
+    fn accumulate_from(&mut self, rhs: &Self) {
+        if rhs.count == 0 {
+            return;
+        }
+
+        if self.count == 0 {
+            *self = *rhs;
+
+            return;
+        }
+
+        let (a, b) = (self.count as f64, rhs.count as f64);
+        let n = a + b;
+        let delta = rhs.mean - self.mean;
+        let delta2 = delta * delta;
+
+        let m2 = self.m2 + rhs.m2 + delta2 * a * b / n;
+        let m3 = self.m3
+            + rhs.m3
+            + delta2 * delta * a * b * (a - b) / (n * n)
+            + 3.0 * delta * (a * rhs.m2 - b * self.m2) / n;
+        let m4 = self.m4
+            + rhs.m4
+            + delta2 * delta2 * a * b * (a * a - a * b + b * b) / (n * n * n)
+            + 6.0 * delta2 * (a * a * rhs.m2 + b * b * self.m2) / (n * n)
+            + 4.0 * delta * (a * rhs.m3 - b * self.m3) / n;
+
+        self.count += rhs.count;
+        self.mean += delta * b / n;
+        self.m2 = m2;
+        self.m3 = m3;
+        self.m4 = m4;
+    }
+}
+
+impl Associative for Moments {}
+
+impl Commutative for Moments {}
+
+#[cfg(test)]
+mod tests {
+    use crate::iter::{Accumulate, TumblingWindow};
+    use crate::Timestamped;
+
+    use super::*;
+
+    const VALUES: [f64; 8] = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn moments() {
+        let moments = VALUES.into_iter().accumulate::<Moments>().unwrap();
+
+        assert_eq!(moments.count(), 8);
+        assert_close(moments.mean(), 5.0);
+        assert_close(moments.variance(), 4.0);
+        assert_close(moments.sample_variance(), 32.0 / 7.0);
+        assert_close(moments.std_dev(), 2.0);
+        assert_close(moments.skewness(), 0.65625);
+        assert_close(moments.kurtosis(), -0.21875);
+    }
+
+    #[test]
+    fn moments_merge() {
+        let sequential = VALUES.into_iter().accumulate::<Moments>().unwrap();
+
+        for split in 1..VALUES.len() {
+            let (head, tail) = VALUES.split_at(split);
+
+            let head = head.iter().copied().accumulate::<Moments>().unwrap();
+            let tail = tail.iter().copied().accumulate::<Moments>().unwrap();
+
+            let merged = head.accumulate(&tail);
+
+            assert_eq!(merged.count(), sequential.count());
+            assert_close(merged.mean(), sequential.mean());
+            assert_close(merged.variance(), sequential.variance());
+            assert_close(merged.skewness(), sequential.skewness());
+            assert_close(merged.kurtosis(), sequential.kurtosis());
+        }
+
+        assert_eq!(Moments::default().accumulate(&sequential), sequential);
+        assert_eq!(sequential.accumulate(&Moments::default()), sequential);
+    }
+
+    #[test]
+    fn moments_empty() {
+        let moments = Moments::default();
+
+        assert!(moments.mean().is_nan());
+        assert!(moments.variance().is_nan());
+        assert!(moments.sample_variance().is_nan());
+        assert!(moments.std_dev().is_nan());
+        assert!(moments.skewness().is_nan());
+        assert!(moments.kurtosis().is_nan());
+        assert!(Moments::from(1.0).sample_variance().is_nan());
+    }
+
+    #[test]
+    fn moments_constant() {
+        let moments = [2.0, 2.0, 2.0].into_iter().accumulate::<Moments>().unwrap();
+
+        assert_eq!(moments.variance(), 0.0);
+        assert!(moments.skewness().is_nan());
+        assert!(moments.kurtosis().is_nan());
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Trade {
+        time: u64,
+        size: f64,
+    }
+
+    impl Timestamped for Trade {
+        type Timestamp = u64;
+
+        fn timestamp(&self) -> u64 {
+            self.time
+        }
+    }
+
+    impl From<Trade> for Moments {
+        fn from(trade: Trade) -> Self {
+            Moments::from(trade.size)
+        }
+    }
+
+    impl Accumulable<Trade> for Moments {
+        fn accumulate_from(&mut self, rhs: &Trade) {
+            self.accumulate_from(&rhs.size);
+        }
+    }
+
+    #[test]
+    fn moments_tumbling_window() {
+        let trades = [(0, 1.0), (10, 3.0), (60, 10.0), (70, 20.0), (80, 30.0)]
+            .map(|(time, size)| Trade { time, size });
+
+        let windows = trades
+            .into_iter()
+            .tumbling_window::<Moments>(60)
+            .map(|(start, moments)| (start, moments.mean(), moments.variance()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(windows, vec![(0, 2.0, 1.0), (60, 20.0, 200.0 / 3.0)]);
+    }
+}
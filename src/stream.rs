@@ -1,6 +1,9 @@
+use std::collections::{BTreeMap, VecDeque};
 use std::mem::replace;
+use std::ops::Range;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use pin_project::pin_project;
 
@@ -8,7 +11,15 @@ use futures::future::{FusedFuture, Future};
 use futures::ready;
 use futures::stream::{FusedStream, Stream};
 
-use crate::{Accumulable, MaybeAccumulable};
+use crate::iter::{Bins, Exact, Identity, Session};
+#[cfg(feature = "tokio")]
+use crate::time::TokioTimer;
+use crate::time::{self, Never, SpanOf, Timer, Timestamp, TimestampOf};
+use crate::window::{Deaccumulate, PaneCombine, Panes, Recombine};
+use crate::{
+    Accumulable, Associative, Commutative, Deaccumulable, Fit, MaybeAccumulable,
+    MaybeAccumulableLookahead, Splittable, Timestamped, Weighable,
+};
 
 pub trait Accumulate<Rhs>: Sized {
     fn accumulate<Lhs>(self) -> Accumulated<Self, Lhs>
@@ -44,12 +55,16 @@ impl<Lhs> AccumulatedState<Lhs> {
         }
     }
 
-    pub fn is_consumed(&self) -> bool {
+    pub fn accumulable(&self) -> Option<&Lhs> {
         match self {
-            AccumulatedState::Consumed => true,
-            _ => false,
+            AccumulatedState::Accumulable(item) => Some(item),
+            _ => None,
         }
     }
+
+    pub fn is_consumed(&self) -> bool {
+        matches!(self, AccumulatedState::Consumed)
+    }
 }
 
 #[pin_project]
@@ -101,8 +116,6 @@ where
                             inner.accumulate_from(&item);
                         }
                         None => {
-                            drop(inner);
-
                             break proj.lhs.consume();
                         }
                     }
@@ -136,6 +149,25 @@ pub trait PartiallyAccumulate<Rhs> {
     where
         Self: Sized,
         Lhs: From<Rhs> + MaybeAccumulable<Rhs>;
+
+    #[cfg(feature = "tokio")]
+    fn partially_accumulate_timeout<Lhs>(
+        self,
+        duration: Duration,
+    ) -> PartiallyAccumulated<Self, Lhs, TokioTimer>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + MaybeAccumulable<Rhs>;
+
+    fn partially_accumulate_timeout_with<Lhs, T>(
+        self,
+        duration: Duration,
+        timer: T,
+    ) -> PartiallyAccumulated<Self, Lhs, T>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + MaybeAccumulable<Rhs>,
+        T: Timer;
 }
 
 impl<S, Rhs> PartiallyAccumulate<Rhs> for S
@@ -150,38 +182,193 @@ where
     {
         PartiallyAccumulated::new(self)
     }
+
+    #[cfg(feature = "tokio")]
+    #[inline]
+    fn partially_accumulate_timeout<Lhs>(
+        self,
+        duration: Duration,
+    ) -> PartiallyAccumulated<S, Lhs, TokioTimer>
+    where
+        S: Sized,
+        Lhs: From<Rhs> + MaybeAccumulable<Rhs>,
+    {
+        PartiallyAccumulated::new(self).idle_timeout(duration)
+    }
+
+    #[inline]
+    fn partially_accumulate_timeout_with<Lhs, T>(
+        self,
+        duration: Duration,
+        timer: T,
+    ) -> PartiallyAccumulated<S, Lhs, T>
+    where
+        S: Sized,
+        Lhs: From<Rhs> + MaybeAccumulable<Rhs>,
+        T: Timer,
+    {
+        PartiallyAccumulated::new(self).idle_timeout_with(duration, timer)
+    }
 }
 
 #[pin_project]
-pub struct PartiallyAccumulated<S, Lhs> {
+pub struct PartiallyAccumulated<S, Lhs, T: Timer = Never> {
     #[pin]
     stream: S,
     lhs: AccumulatedState<Lhs>,
+    items: usize,
+    max_items: usize,
+    timer: T,
+    idle_timeout: Option<Duration>,
+    max_age: Option<Duration>,
+    flush_on_pending: bool,
+    #[pin]
+    idle: Option<T::Delay>,
+    #[pin]
+    deadline: Option<T::Delay>,
 }
 
 impl<S, Lhs> PartiallyAccumulated<S, Lhs> {
     pub fn new(stream: S) -> Self {
+        Self::restore(stream, None)
+    }
+
+    /// Resumes from a group previously taken with [`snapshot`](Self::snapshot).
+    pub fn restore(stream: S, lhs: Option<Lhs>) -> Self {
         Self {
             stream,
-            lhs: AccumulatedState::Uninit,
+            items: usize::from(lhs.is_some()),
+            lhs: match lhs {
+                Some(lhs) => AccumulatedState::Accumulable(lhs),
+                None => AccumulatedState::Uninit,
+            },
+            max_items: usize::MAX,
+            timer: Never,
+            idle_timeout: None,
+            max_age: None,
+            flush_on_pending: false,
+            idle: None,
+            deadline: None,
+        }
+    }
+}
+
+impl<S, Lhs, T> PartiallyAccumulated<S, Lhs, T>
+where
+    T: Timer,
+{
+    /// The group currently open, i.e. accumulated but not yet emitted.
+    pub fn snapshot(&self) -> Option<&Lhs> {
+        self.lhs.accumulable()
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut S> {
+        self.project().stream
+    }
+
+    /// Emits the open group as soon as upstream has no item ready, merging only
+    /// what is immediately available.
+    pub fn flush_on_pending(mut self) -> Self {
+        self.flush_on_pending = true;
+
+        self
+    }
+
+    /// Closes a group once it holds `max_items` items, whatever
+    /// `maybe_accumulate_from` would decide.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        assert!(max_items > 0, "max_items must be positive");
+
+        self.max_items = max_items;
+
+        self
+    }
+
+    /// Closes a group once `max_merges` items have been merged into its first.
+    pub fn max_merges(self, max_merges: usize) -> Self {
+        self.max_items(max_merges.saturating_add(1))
+    }
+
+    /// Emits the open group once no item has arrived for `duration`.
+    #[cfg(feature = "tokio")]
+    pub fn idle_timeout(self, duration: Duration) -> PartiallyAccumulated<S, Lhs, TokioTimer> {
+        self.idle_timeout_with(duration, TokioTimer)
+    }
+
+    pub fn idle_timeout_with<U>(
+        self,
+        duration: Duration,
+        timer: U,
+    ) -> PartiallyAccumulated<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        PartiallyAccumulated {
+            idle_timeout: Some(duration),
+            ..self.with_timer(timer)
+        }
+    }
+
+    /// Emits the open group once `duration` has passed since its first item,
+    /// even if items keep merging into it.
+    #[cfg(feature = "tokio")]
+    pub fn max_group_age(self, duration: Duration) -> PartiallyAccumulated<S, Lhs, TokioTimer> {
+        self.max_group_age_with(duration, TokioTimer)
+    }
+
+    pub fn max_group_age_with<U>(
+        self,
+        duration: Duration,
+        timer: U,
+    ) -> PartiallyAccumulated<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        PartiallyAccumulated {
+            max_age: Some(duration),
+            ..self.with_timer(timer)
+        }
+    }
+
+    fn with_timer<U>(self, timer: U) -> PartiallyAccumulated<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        PartiallyAccumulated {
+            stream: self.stream,
+            lhs: self.lhs,
+            items: self.items,
+            max_items: self.max_items,
+            timer,
+            idle_timeout: self.idle_timeout,
+            max_age: self.max_age,
+            flush_on_pending: self.flush_on_pending,
+            idle: None,
+            deadline: None,
         }
     }
 }
 
-impl<S, Lhs> FusedStream for PartiallyAccumulated<S, Lhs>
+impl<S, Lhs, T> FusedStream for PartiallyAccumulated<S, Lhs, T>
 where
     S: Stream,
     Lhs: From<S::Item> + MaybeAccumulable<S::Item>,
+    T: Timer,
 {
     fn is_terminated(&self) -> bool {
         self.lhs.is_consumed()
     }
 }
 
-impl<S, Lhs> Stream for PartiallyAccumulated<S, Lhs>
+impl<S, Lhs, T> Stream for PartiallyAccumulated<S, Lhs, T>
 where
     S: Stream,
     Lhs: From<S::Item> + MaybeAccumulable<S::Item>,
+    T: Timer,
 {
     type Item = Lhs;
 
@@ -198,25 +385,73 @@ where
                     match first {
                         Some(first) => {
                             *proj.lhs = AccumulatedState::Accumulable(Lhs::from(first));
+                            *proj.items = 1;
+
+                            time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
                         }
                         None => break proj.lhs.consume(),
                     }
                 }
                 S::Accumulable(inner) => {
-                    let item = ready!(proj.stream.as_mut().poll_next(cx));
+                    if *proj.items >= *proj.max_items {
+                        proj.idle.set(None);
+                        proj.deadline.set(None);
+
+                        break proj.lhs.reinit();
+                    }
+
+                    if proj.max_age.is_some() && proj.deadline.is_none() {
+                        time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
+                    }
+
+                    if time::poll_elapsed(proj.deadline.as_mut(), cx) {
+                        proj.idle.set(None);
+                        proj.deadline.set(None);
+
+                        break proj.lhs.reinit();
+                    }
+
+                    let item = match proj.stream.as_mut().poll_next(cx) {
+                        Poll::Ready(item) => item,
+                        Poll::Pending if *proj.flush_on_pending => {
+                            proj.deadline.set(None);
+
+                            break proj.lhs.reinit();
+                        }
+                        Poll::Pending => {
+                            if proj.idle.is_none() {
+                                time::reset(proj.idle.as_mut(), proj.timer, *proj.idle_timeout);
+                            }
+
+                            if time::poll_elapsed(proj.idle.as_mut(), cx) {
+                                proj.idle.set(None);
+                                proj.deadline.set(None);
+
+                                break proj.lhs.reinit();
+                            }
+
+                            return Poll::Pending;
+                        }
+                    };
+
+                    proj.idle.set(None);
 
                     match item {
                         Some(item) => {
                             if inner.maybe_accumulate_from(&item) {
+                                *proj.items += 1;
+
                                 continue;
                             } else {
-                                drop(inner);
+                                *proj.items = 1;
+
+                                time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
 
                                 break proj.lhs.reaccumulable(Lhs::from(item));
                             }
                         }
                         None => {
-                            drop(inner);
+                            proj.deadline.set(None);
 
                             break proj.lhs.reinit();
                         }
@@ -230,118 +465,1454 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use futures::stream::{self, StreamExt};
+pub trait PartiallyAccumulateLookahead<Rhs>: Stream<Item = Rhs> {
+    fn partially_accumulate_lookahead<Lhs>(
+        self,
+        lookahead: usize,
+    ) -> PartiallyAccumulatedLookahead<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + MaybeAccumulableLookahead<Rhs>;
+}
 
-    use super::*;
+impl<S> PartiallyAccumulateLookahead<S::Item> for S
+where
+    S: Stream,
+{
+    #[inline]
+    fn partially_accumulate_lookahead<Lhs>(
+        self,
+        lookahead: usize,
+    ) -> PartiallyAccumulatedLookahead<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + MaybeAccumulableLookahead<S::Item>,
+    {
+        PartiallyAccumulatedLookahead::new(self, lookahead)
+    }
+}
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
-    pub struct Volume(u64);
+/// Partial accumulation whose decisions see up to `lookahead` items after the
+/// candidate; fewer only once the stream has ended. A decision waits until
+/// that many items have arrived.
+#[pin_project]
+pub struct PartiallyAccumulatedLookahead<S, Lhs>
+where
+    S: Stream,
+{
+    #[pin]
+    stream: S,
+    lhs: Option<Lhs>,
+    buffer: VecDeque<S::Item>,
+    lookahead: usize,
+    terminated: bool,
+}
 
-    impl Accumulable for Volume {
-        fn accumulate_from(&mut self, rhs: &Self) {
-            *self = Volume(self.0 + rhs.0);
+impl<S, Lhs> PartiallyAccumulatedLookahead<S, Lhs>
+where
+    S: Stream,
+{
+    pub fn new(stream: S, lookahead: usize) -> Self {
+        Self {
+            stream,
+            lhs: None,
+            buffer: VecDeque::with_capacity(lookahead.saturating_add(1)),
+            lookahead,
+            terminated: false,
         }
     }
+}
 
-    #[tokio::test]
-    async fn test_accumulate_zero() {
-        let volumes = stream::iter([]);
-
-        assert_eq!(volumes.accumulate::<Volume>().await, None);
+impl<S, Lhs> FusedStream for PartiallyAccumulatedLookahead<S, Lhs>
+where
+    S: Stream,
+    Lhs: From<S::Item> + MaybeAccumulableLookahead<S::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.lhs.is_none() && self.buffer.is_empty()
     }
+}
 
-    #[tokio::test]
-    async fn test_accumulate_one() {
-        let volumes = stream::iter([Volume(10)]);
-
-        assert_eq!(volumes.accumulate::<Volume>().await.unwrap(), Volume(10));
-    }
+impl<S, Lhs> Stream for PartiallyAccumulatedLookahead<S, Lhs>
+where
+    S: Stream,
+    Lhs: From<S::Item> + MaybeAccumulableLookahead<S::Item>,
+{
+    type Item = Lhs;
 
-    #[tokio::test]
-    async fn test_accumulate() {
-        let volumes = stream::iter([Volume(10), Volume(15), Volume(20), Volume(25), Volume(30)]);
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
 
-        assert_eq!(volumes.accumulate::<Volume>().await.unwrap(), Volume(100));
-    }
+        loop {
+            while !*proj.terminated && proj.buffer.len() <= *proj.lookahead {
+                match ready!(proj.stream.as_mut().poll_next(cx)) {
+                    Some(rhs) => proj.buffer.push_back(rhs),
+                    None => *proj.terminated = true,
+                }
+            }
 
-    #[derive(Clone, Copy, Debug, PartialEq)]
-    pub enum VolumeSize<const N: u64> {
-        Large(Volume),
-        Small(Volume),
-    }
+            let Some(lhs) = proj.lhs else {
+                match proj.buffer.pop_front() {
+                    Some(first) => *proj.lhs = Some(Lhs::from(first)),
+                    None => return Poll::Ready(None),
+                }
 
-    impl<const N: u64> VolumeSize<N> {
-        pub fn new(volume: Volume) -> Self {
-            if volume.0 >= N {
-                Self::Large(volume)
-            } else {
-                Self::Small(volume)
-            }
-        }
+                continue;
+            };
 
-        pub fn volume_value(&self) -> Volume {
-            match self {
-                Self::Large(x) | Self::Small(x) => *x,
+            match proj.buffer.make_contiguous().split_first() {
+                Some((rhs, lookahead)) if lhs.maybe_accumulate_from_lookahead(rhs, lookahead) => {
+                    drop(proj.buffer.pop_front());
+                }
+                _ => return Poll::Ready(proj.lhs.take()),
             }
         }
     }
+}
 
-    impl<const N: u64> Accumulable for VolumeSize<N> {
-        fn accumulate_from(&mut self, rhs: &Self) {
-            *self = VolumeSize::new(self.volume_value().accumulate(&rhs.volume_value()))
-        }
+pub trait AccumulateUpToWeight<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Weighable,
+{
+    fn accumulate_up_to_weight<Lhs>(self, max_weight: usize) -> AccumulatedUpToWeight<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<S> AccumulateUpToWeight<S::Item> for S
+where
+    S: Stream,
+    S::Item: Weighable,
+{
+    #[inline]
+    fn accumulate_up_to_weight<Lhs>(self, max_weight: usize) -> AccumulatedUpToWeight<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        AccumulatedUpToWeight::new(self, max_weight)
     }
+}
 
-    impl<const N: u64> MaybeAccumulable for VolumeSize<N> {
-        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
-            match self {
-                VolumeSize::Small(_) => {
-                    self.accumulate_from(rhs);
+/// Groups consecutive items while their total weight stays within
+/// `max_weight`; an item heavier than that on its own is yielded alone.
+///
+/// A group is yielded as soon as it reaches `max_weight`, without waiting for
+/// the next item.
+#[pin_project]
+pub struct AccumulatedUpToWeight<S, Lhs, T: Timer = Never> {
+    #[pin]
+    stream: S,
+    lhs: AccumulatedState<Lhs>,
+    weight: usize,
+    items: usize,
+    max_weight: usize,
+    max_items: usize,
+    timer: T,
+    max_age: Option<Duration>,
+    #[pin]
+    deadline: Option<T::Delay>,
+}
 
-                    true
-                }
-                _ => false,
-            }
+impl<S, Lhs> AccumulatedUpToWeight<S, Lhs> {
+    pub fn new(stream: S, max_weight: usize) -> Self {
+        Self {
+            stream,
+            lhs: AccumulatedState::Uninit,
+            weight: 0,
+            items: 0,
+            max_weight,
+            max_items: usize::MAX,
+            timer: Never,
+            max_age: None,
+            deadline: None,
         }
     }
+}
 
-    #[tokio::test]
-    async fn partially_accumulate_zero() {
-        type VolumeSize100 = VolumeSize<100>;
-
-        let volumes = stream::iter([]);
+impl<S, Lhs, T> AccumulatedUpToWeight<S, Lhs, T>
+where
+    T: Timer,
+{
+    /// Also closes a group once it holds `max_items` items.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        assert!(max_items > 0, "max_items must be positive");
 
-        let partially_accumulated = volumes
-            .partially_accumulate::<VolumeSize100>()
-            .collect::<Vec<_>>()
-            .await;
+        self.max_items = max_items;
 
-        assert_eq!(partially_accumulated, vec![])
+        self
     }
 
-    #[tokio::test]
-    async fn partially_accumulate_one() {
-        type VolumeSize100 = VolumeSize<100>;
-
-        let volumes = stream::iter([VolumeSize100::new(Volume(60))]);
+    /// Also closes a group once `duration` has passed since its first item.
+    #[cfg(feature = "tokio")]
+    pub fn max_group_age(self, duration: Duration) -> AccumulatedUpToWeight<S, Lhs, TokioTimer> {
+        self.max_group_age_with(duration, TokioTimer)
+    }
 
-        let partially_accumulated = volumes
-            .partially_accumulate::<VolumeSize100>()
-            .fuse()
-            .collect::<Vec<_>>()
-            .await;
+    pub fn max_group_age_with<U>(
+        self,
+        duration: Duration,
+        timer: U,
+    ) -> AccumulatedUpToWeight<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        AccumulatedUpToWeight {
+            stream: self.stream,
+            lhs: self.lhs,
+            weight: self.weight,
+            items: self.items,
+            max_weight: self.max_weight,
+            max_items: self.max_items,
+            timer,
+            max_age: Some(duration),
+            deadline: None,
+        }
+    }
+}
 
-        assert_eq!(
-            partially_accumulated,
-            vec![VolumeSize100::Small(Volume(60))]
-        )
+impl<S, Lhs, T> FusedStream for AccumulatedUpToWeight<S, Lhs, T>
+where
+    S: Stream,
+    S::Item: Weighable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.lhs.is_consumed()
     }
+}
 
-    #[tokio::test]
-    async fn partially_accumulate() {
-        type VolumeSize100 = VolumeSize<100>;
+impl<S, Lhs, T> Stream for AccumulatedUpToWeight<S, Lhs, T>
+where
+    S: Stream,
+    S::Item: Weighable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+    T: Timer,
+{
+    type Item = Lhs;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        use AccumulatedState as S;
+
+        let mut proj = self.project();
+
+        let result = loop {
+            match proj.lhs {
+                S::Uninit => {
+                    let first = ready!(proj.stream.as_mut().poll_next(cx));
+
+                    match first {
+                        Some(first) => {
+                            *proj.weight = first.weight();
+                            *proj.items = 1;
+                            *proj.lhs = AccumulatedState::Accumulable(Lhs::from(first));
+
+                            time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
+                        }
+                        None => break proj.lhs.consume(),
+                    }
+                }
+                S::Accumulable(inner) => {
+                    if *proj.weight >= *proj.max_weight
+                        || *proj.items >= *proj.max_items
+                        || time::poll_elapsed(proj.deadline.as_mut(), cx)
+                    {
+                        proj.deadline.set(None);
+
+                        break proj.lhs.reinit();
+                    }
+
+                    let item = ready!(proj.stream.as_mut().poll_next(cx));
+
+                    match item {
+                        Some(item) => {
+                            let weight = item.weight();
+
+                            if proj.weight.saturating_add(weight) <= *proj.max_weight {
+                                *proj.weight += weight;
+                                *proj.items += 1;
+
+                                inner.accumulate_from(&item);
+                            } else {
+                                *proj.weight = weight;
+                                *proj.items = 1;
+
+                                time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
+
+                                break proj.lhs.reaccumulable(Lhs::from(item));
+                            }
+                        }
+                        None => {
+                            proj.deadline.set(None);
+
+                            break proj.lhs.reinit();
+                        }
+                    }
+                }
+                S::Consumed => panic!("Accumulated polled after completion"),
+            }
+        };
+
+        Poll::Ready(result)
+    }
+}
+
+pub trait PartiallyAccumulateExact<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Weighable + Splittable,
+{
+    fn partially_accumulate_exact<Lhs>(
+        self,
+        capacity: usize,
+    ) -> PartiallyAccumulatedExact<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<S> PartiallyAccumulateExact<S::Item> for S
+where
+    S: Stream,
+    S::Item: Weighable + Splittable,
+{
+    #[inline]
+    fn partially_accumulate_exact<Lhs>(
+        self,
+        capacity: usize,
+    ) -> PartiallyAccumulatedExact<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        PartiallyAccumulatedExact::new(self, capacity)
+    }
+}
+
+/// Groups items into exactly `capacity` of weight each, splitting an item that
+/// overflows a group and carrying the remainder forward. Only the last group
+/// may be lighter.
+#[pin_project]
+pub struct PartiallyAccumulatedExact<S, Lhs>
+where
+    S: Stream,
+{
+    #[pin]
+    stream: S,
+    exact: Exact<S::Item, Lhs>,
+    terminated: bool,
+}
+
+impl<S, Lhs> PartiallyAccumulatedExact<S, Lhs>
+where
+    S: Stream,
+    S::Item: Weighable + Splittable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    pub fn new(stream: S, capacity: usize) -> Self {
+        Self {
+            stream,
+            exact: Exact::new(capacity),
+            terminated: false,
+        }
+    }
+}
+
+impl<S, Lhs> FusedStream for PartiallyAccumulatedExact<S, Lhs>
+where
+    S: Stream,
+    S::Item: Weighable + Splittable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.exact.is_empty()
+    }
+}
+
+impl<S, Lhs> Stream for PartiallyAccumulatedExact<S, Lhs>
+where
+    S: Stream,
+    S::Item: Weighable + Splittable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    type Item = Lhs;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        loop {
+            if let Some(lhs) = proj.exact.pop_full() {
+                return Poll::Ready(Some(lhs));
+            }
+
+            if *proj.terminated {
+                return Poll::Ready(proj.exact.flush());
+            }
+
+            match ready!(proj.stream.as_mut().poll_next(cx)) {
+                Some(rhs) => proj.exact.push(rhs),
+                None => *proj.terminated = true,
+            }
+        }
+    }
+}
+
+pub trait BinPack<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Weighable,
+{
+    fn bin_pack<Lhs>(self, capacity: usize, fit: Fit) -> BinPacked<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<S> BinPack<S::Item> for S
+where
+    S: Stream,
+    S::Item: Weighable,
+{
+    #[inline]
+    fn bin_pack<Lhs>(self, capacity: usize, fit: Fit) -> BinPacked<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        BinPacked::new(self, capacity, fit)
+    }
+}
+
+/// Packs items, in any order, into bins of `capacity` weight. Bins are yielded
+/// as soon as they fill up; the rest stay open until the stream ends, unless
+/// bounded with [`open_bins`](Self::open_bins).
+#[pin_project]
+pub struct BinPacked<S, Lhs> {
+    #[pin]
+    stream: S,
+    bins: Bins<Lhs>,
+    terminated: bool,
+}
+
+impl<S, Lhs> BinPacked<S, Lhs> {
+    pub fn new(stream: S, capacity: usize, fit: Fit) -> Self {
+        Self {
+            stream,
+            bins: Bins::new(capacity, fit),
+            terminated: false,
+        }
+    }
+
+    /// Keeps at most `max_open` bins open, yielding the fullest one when an
+    /// item fits none of them.
+    pub fn open_bins(mut self, max_open: usize) -> Self {
+        self.bins.set_max_open(max_open);
+
+        self
+    }
+}
+
+impl<S, Lhs> FusedStream for BinPacked<S, Lhs>
+where
+    S: Stream,
+    S::Item: Weighable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.bins.is_empty()
+    }
+}
+
+impl<S, Lhs> Stream for BinPacked<S, Lhs>
+where
+    S: Stream,
+    S::Item: Weighable,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    type Item = Lhs;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        loop {
+            if let Some(lhs) = proj.bins.pop_closed() {
+                return Poll::Ready(Some(lhs));
+            }
+
+            if *proj.terminated {
+                return Poll::Ready(None);
+            }
+
+            match ready!(proj.stream.as_mut().poll_next(cx)) {
+                Some(rhs) => proj.bins.push(rhs),
+                None => {
+                    *proj.terminated = true;
+
+                    proj.bins.flush();
+                }
+            }
+        }
+    }
+}
+
+pub trait TumblingWindow<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn tumbling_window<Lhs>(self, size: SpanOf<Rhs>) -> TumblingWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<S> TumblingWindow<S::Item> for S
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[inline]
+    fn tumbling_window<Lhs>(self, size: SpanOf<S::Item>) -> TumblingWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        TumblingWindows::new(self, size)
+    }
+}
+
+/// Folds items into fixed windows aligned to multiples of `size`, yielding
+/// `(window_start, Lhs)`.
+///
+/// Items are expected in timestamp order; an item earlier than the open window
+/// is accumulated into it.
+#[pin_project]
+pub struct TumblingWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[pin]
+    stream: S,
+    size: SpanOf<S::Item>,
+    open: Option<(TimestampOf<S::Item>, Lhs)>,
+    empty: Option<Range<TimestampOf<S::Item>>>,
+    identity: Option<Identity<Lhs>>,
+    terminated: bool,
+}
+
+impl<S, Lhs> TumblingWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    pub fn new(stream: S, size: SpanOf<S::Item>) -> Self {
+        assert!(size > Default::default(), "window size must be positive");
+
+        Self {
+            stream,
+            size,
+            open: None,
+            empty: None,
+            identity: None,
+            terminated: false,
+        }
+    }
+
+    /// Yields `identity` for every window without items between two non-empty ones.
+    pub fn fill_empty(mut self, identity: Lhs) -> Self
+    where
+        Lhs: Clone,
+    {
+        self.identity = Some(Identity::new(identity));
+
+        self
+    }
+}
+
+impl<S, Lhs> FusedStream for TumblingWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+impl<S, Lhs> Stream for TumblingWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+{
+    type Item = (TimestampOf<S::Item>, Lhs);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        if let (Some(empty), Some(identity)) = (&mut *proj.empty, &*proj.identity) {
+            if empty.start < empty.end {
+                let start = empty.start;
+
+                empty.start = start.add_span(*proj.size);
+
+                return Poll::Ready(Some((start, identity.get())));
+            }
+
+            *proj.empty = None;
+        }
+
+        if *proj.terminated {
+            return Poll::Ready(None);
+        }
+
+        let result = loop {
+            let rhs = ready!(proj.stream.as_mut().poll_next(cx));
+
+            match rhs {
+                Some(rhs) => {
+                    let start = rhs.timestamp().floor(*proj.size);
+
+                    match proj.open {
+                        Some((open, lhs)) if start <= *open => lhs.accumulate_from(&rhs),
+                        _ => {
+                            if let Some((previous, lhs)) =
+                                proj.open.replace((start, Lhs::from(rhs)))
+                            {
+                                *proj.empty = Some(previous.add_span(*proj.size)..start);
+
+                                break Some((previous, lhs));
+                            }
+                        }
+                    }
+                }
+                None => {
+                    *proj.terminated = true;
+
+                    break proj.open.take();
+                }
+            }
+        };
+
+        Poll::Ready(result)
+    }
+}
+
+pub trait EventTimeWindow<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn event_time_window<Lhs>(self, size: SpanOf<Rhs>) -> EventTimeWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs> + Clone;
+}
+
+impl<S> EventTimeWindow<S::Item> for S
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[inline]
+    fn event_time_window<Lhs>(self, size: SpanOf<S::Item>) -> EventTimeWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item> + Clone,
+    {
+        EventTimeWindows::new(self, size)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowEvent<T, Lhs, Rhs> {
+    /// The watermark passed the end of the window.
+    Closed(T, Lhs),
+    /// A closed window received an item within the allowed lateness.
+    Updated(T, Lhs),
+    /// An item arrived after its window was past the allowed lateness.
+    Late(Rhs),
+}
+
+struct Watermarked<Rhs, Lhs>
+where
+    Rhs: Timestamped,
+{
+    size: SpanOf<Rhs>,
+    out_of_orderness: Option<SpanOf<Rhs>>,
+    lateness: Option<SpanOf<Rhs>>,
+    watermark: Option<TimestampOf<Rhs>>,
+    windows: BTreeMap<TimestampOf<Rhs>, (Lhs, bool)>,
+    events: VecDeque<WindowEvent<TimestampOf<Rhs>, Lhs, Rhs>>,
+}
+
+impl<Rhs, Lhs> Watermarked<Rhs, Lhs>
+where
+    Rhs: Timestamped,
+    Lhs: From<Rhs> + Accumulable<Rhs> + Clone,
+{
+    fn expiry(&self, start: TimestampOf<Rhs>) -> TimestampOf<Rhs> {
+        let end = start.add_span(self.size);
+
+        match self.lateness {
+            Some(lateness) => end.add_span(lateness),
+            None => end,
+        }
+    }
+
+    fn push(&mut self, rhs: Rhs) {
+        let timestamp = rhs.timestamp();
+        let start = timestamp.floor(self.size);
+
+        if self.watermark >= Some(self.expiry(start)) {
+            self.events.push_back(WindowEvent::Late(rhs));
+
+            return;
+        }
+
+        match self.windows.get_mut(&start) {
+            Some((lhs, closed)) => {
+                lhs.accumulate_from(&rhs);
+
+                if *closed {
+                    self.events
+                        .push_back(WindowEvent::Updated(start, lhs.clone()));
+                }
+            }
+            None => {
+                let lhs = Lhs::from(rhs);
+
+                // The watermark may already be past a window only created now.
+                if self.watermark >= Some(start.add_span(self.size)) {
+                    self.events
+                        .push_back(WindowEvent::Closed(start, lhs.clone()));
+                    self.windows.insert(start, (lhs, true));
+                } else {
+                    self.windows.insert(start, (lhs, false));
+                }
+            }
+        }
+
+        let watermark = match self.out_of_orderness {
+            Some(out_of_orderness) => timestamp.sub_span(out_of_orderness),
+            None => timestamp,
+        };
+
+        if self.watermark < Some(watermark) {
+            self.watermark = Some(watermark);
+
+            self.advance(watermark);
+        }
+    }
+
+    fn advance(&mut self, watermark: TimestampOf<Rhs>) {
+        let mut expired = Vec::new();
+
+        for (&start, (lhs, closed)) in self.windows.iter_mut() {
+            if start.add_span(self.size) > watermark {
+                break;
+            }
+
+            let expiry = match self.lateness {
+                Some(lateness) => start.add_span(self.size).add_span(lateness),
+                None => start.add_span(self.size),
+            };
+
+            if !*closed {
+                *closed = true;
+
+                self.events
+                    .push_back(WindowEvent::Closed(start, lhs.clone()));
+            }
+
+            if expiry <= watermark {
+                expired.push(start);
+            }
+        }
+
+        for start in expired {
+            self.windows.remove(&start);
+        }
+    }
+
+    fn flush(&mut self) {
+        for (start, (lhs, closed)) in std::mem::take(&mut self.windows) {
+            if !closed {
+                self.events.push_back(WindowEvent::Closed(start, lhs));
+            }
+        }
+    }
+}
+
+/// Tumbling windows closed by a watermark instead of by the next window's first
+/// item, so that out-of-order items still land in their own window.
+///
+/// The watermark trails the latest timestamp seen by the maximum
+/// out-of-orderness. Windows are kept for the allowed lateness past their end,
+/// and re-emitted as [`WindowEvent::Updated`] for every late item they receive.
+#[pin_project]
+pub struct EventTimeWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[pin]
+    stream: S,
+    windows: Watermarked<S::Item, Lhs>,
+    terminated: bool,
+}
+
+impl<S, Lhs> EventTimeWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    pub fn new(stream: S, size: SpanOf<S::Item>) -> Self {
+        assert!(size > Default::default(), "window size must be positive");
+
+        Self {
+            stream,
+            windows: Watermarked {
+                size,
+                out_of_orderness: None,
+                lateness: None,
+                watermark: None,
+                windows: BTreeMap::new(),
+                events: VecDeque::new(),
+            },
+            terminated: false,
+        }
+    }
+
+    pub fn max_out_of_orderness(mut self, span: SpanOf<S::Item>) -> Self {
+        self.windows.out_of_orderness = Some(span);
+
+        self
+    }
+
+    pub fn allowed_lateness(mut self, span: SpanOf<S::Item>) -> Self {
+        self.windows.lateness = Some(span);
+
+        self
+    }
+}
+
+impl<S, Lhs> FusedStream for EventTimeWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item> + Clone,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.windows.events.is_empty()
+    }
+}
+
+impl<S, Lhs> Stream for EventTimeWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item> + Clone,
+{
+    type Item = WindowEvent<TimestampOf<S::Item>, Lhs, S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        loop {
+            if let Some(event) = proj.windows.events.pop_front() {
+                return Poll::Ready(Some(event));
+            }
+
+            if *proj.terminated {
+                return Poll::Ready(None);
+            }
+
+            let rhs = ready!(proj.stream.as_mut().poll_next(cx));
+
+            match rhs {
+                Some(rhs) => proj.windows.push(rhs),
+                None => {
+                    *proj.terminated = true;
+
+                    proj.windows.flush();
+                }
+            }
+        }
+    }
+}
+
+pub trait SessionWindow<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn session_windows<Lhs>(self, gap: SpanOf<Rhs>) -> SessionWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<S> SessionWindow<S::Item> for S
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[inline]
+    fn session_windows<Lhs>(self, gap: SpanOf<S::Item>) -> SessionWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item>,
+    {
+        SessionWindows::new(self, gap)
+    }
+}
+
+/// Groups items into sessions, yielding `(session_start, session_end, Lhs)`.
+///
+/// A session goes on while each item is at most `gap` after the latest
+/// timestamp seen in it; the end is the timestamp of that latest item. With an
+/// idle timeout, a session is also closed when no item arrives in time.
+#[pin_project]
+pub struct SessionWindows<S, Lhs, T: Timer = Never>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[pin]
+    stream: S,
+    gap: SpanOf<S::Item>,
+    open: Option<Session<TimestampOf<S::Item>, Lhs>>,
+    terminated: bool,
+    timer: T,
+    idle_timeout: Option<Duration>,
+    #[pin]
+    idle: Option<T::Delay>,
+}
+
+impl<S, Lhs> SessionWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    pub fn new(stream: S, gap: SpanOf<S::Item>) -> Self {
+        assert!(gap > Default::default(), "session gap must be positive");
+
+        Self {
+            stream,
+            gap,
+            open: None,
+            terminated: false,
+            timer: Never,
+            idle_timeout: None,
+            idle: None,
+        }
+    }
+}
+
+impl<S, Lhs, T> SessionWindows<S, Lhs, T>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    T: Timer,
+{
+    #[cfg(feature = "tokio")]
+    pub fn idle_timeout(self, duration: Duration) -> SessionWindows<S, Lhs, TokioTimer> {
+        self.idle_timeout_with(duration, TokioTimer)
+    }
+
+    pub fn idle_timeout_with<U>(self, duration: Duration, timer: U) -> SessionWindows<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        SessionWindows {
+            stream: self.stream,
+            gap: self.gap,
+            open: self.open,
+            terminated: self.terminated,
+            timer,
+            idle_timeout: Some(duration),
+            idle: None,
+        }
+    }
+}
+
+impl<S, Lhs, T> FusedStream for SessionWindows<S, Lhs, T>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+    T: Timer,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated
+    }
+}
+
+impl<S, Lhs, T> Stream for SessionWindows<S, Lhs, T>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+    T: Timer,
+{
+    type Item = (TimestampOf<S::Item>, TimestampOf<S::Item>, Lhs);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        if *proj.terminated {
+            return Poll::Ready(None);
+        }
+
+        let result = loop {
+            let rhs = match proj.stream.as_mut().poll_next(cx) {
+                Poll::Ready(rhs) => rhs,
+                Poll::Pending => {
+                    if proj.open.is_some() {
+                        if proj.idle.is_none() {
+                            time::reset(proj.idle.as_mut(), proj.timer, *proj.idle_timeout);
+                        }
+
+                        if time::poll_elapsed(proj.idle.as_mut(), cx) {
+                            proj.idle.set(None);
+
+                            break proj.open.take();
+                        }
+                    }
+
+                    return Poll::Pending;
+                }
+            };
+
+            proj.idle.set(None);
+
+            match rhs {
+                Some(rhs) => {
+                    let accumulated = match proj.open {
+                        Some(session) => session.maybe_accumulate_from(&rhs, *proj.gap),
+                        None => false,
+                    };
+
+                    if !accumulated {
+                        if let Some(session) = proj.open.replace(Session::new(rhs)) {
+                            break Some(session);
+                        }
+                    }
+                }
+                None => {
+                    *proj.terminated = true;
+
+                    break proj.open.take();
+                }
+            }
+        };
+
+        Poll::Ready(result.map(Session::into_tuple))
+    }
+}
+
+pub trait SlidingTimeWindow<Rhs>: Stream<Item = Rhs>
+where
+    Rhs: Timestamped,
+{
+    fn sliding_time_window<Lhs>(
+        self,
+        size: SpanOf<Rhs>,
+        hop: SpanOf<Rhs>,
+    ) -> SlidingTimeWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<Rhs> + Accumulable<Rhs> + Associative + Clone;
+}
+
+impl<S> SlidingTimeWindow<S::Item> for S
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[inline]
+    fn sliding_time_window<Lhs>(
+        self,
+        size: SpanOf<S::Item>,
+        hop: SpanOf<S::Item>,
+    ) -> SlidingTimeWindows<Self, Lhs>
+    where
+        Self: Sized,
+        Lhs: From<S::Item> + Accumulable<S::Item> + Associative + Clone,
+    {
+        SlidingTimeWindows::new(self, size, hop)
+    }
+}
+
+/// Windows of `size` starting every `hop`, yielding `(window_start, Lhs)` for
+/// every window holding at least one item.
+///
+/// Items are folded once into `hop` wide panes, which are then combined per
+/// window. Panes are never split, so a `size` that is not a multiple of `hop`
+/// is rounded up to the next one. Items are expected in timestamp order; an
+/// item earlier than the latest pane is accumulated into it.
+///
+/// Panics if `hop` is not positive or `size` is less than `hop`.
+#[pin_project]
+pub struct SlidingTimeWindows<S, Lhs, C = Recombine>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    #[pin]
+    stream: S,
+    panes: Panes<S::Item, Lhs, C>,
+    terminated: bool,
+}
+
+impl<S, Lhs> SlidingTimeWindows<S, Lhs>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    pub fn new(stream: S, size: SpanOf<S::Item>, hop: SpanOf<S::Item>) -> Self {
+        Self {
+            stream,
+            panes: Panes::new(size, hop, Recombine),
+            terminated: false,
+        }
+    }
+}
+
+impl<S, Lhs, C> SlidingTimeWindows<S, Lhs, C>
+where
+    S: Stream,
+    S::Item: Timestamped,
+{
+    /// Maintains a running total with [`Deaccumulable`] instead of combining
+    /// all panes of every window.
+    pub fn deaccumulate(self) -> SlidingTimeWindows<S, Lhs, Deaccumulate<Lhs>>
+    where
+        Lhs: Commutative + Deaccumulable,
+    {
+        SlidingTimeWindows {
+            stream: self.stream,
+            panes: self.panes.with_combine(Deaccumulate::default()),
+            terminated: self.terminated,
+        }
+    }
+}
+
+impl<S, Lhs, C> FusedStream for SlidingTimeWindows<S, Lhs, C>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+    C: PaneCombine<Lhs>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.panes.is_empty()
+    }
+}
+
+impl<S, Lhs, C> Stream for SlidingTimeWindows<S, Lhs, C>
+where
+    S: Stream,
+    S::Item: Timestamped,
+    Lhs: From<S::Item> + Accumulable<S::Item>,
+    C: PaneCombine<Lhs>,
+{
+    type Item = (TimestampOf<S::Item>, Lhs);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        loop {
+            if let Some(window) = proj.panes.pop_ready() {
+                return Poll::Ready(Some(window));
+            }
+
+            if *proj.terminated {
+                return Poll::Ready(None);
+            }
+
+            let rhs = ready!(proj.stream.as_mut().poll_next(cx));
+
+            match rhs {
+                Some(rhs) => proj.panes.push(rhs),
+                None => {
+                    *proj.terminated = true;
+
+                    proj.panes.flush();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::channel::mpsc;
+    use futures::stream::{self, StreamExt};
+    use futures::FutureExt;
+
+    use tokio::time::Instant;
+
+    use crate::time::tests::TestTimer;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u64);
+
+    impl Accumulable for Volume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = Volume(self.0 + rhs.0);
+        }
+    }
+
+    impl Weighable for Volume {
+        fn weight(&self) -> usize {
+            self.0 as usize
+        }
+    }
+
+    #[tokio::test]
+    async fn accumulate_up_to_weight() {
+        let volumes =
+            stream::iter([60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1, 150, 20].map(Volume));
+
+        let batches = volumes
+            .accumulate_up_to_weight::<Volume>(100)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(batches, [90, 55, 70, 100, 2, 150, 20].map(Volume).to_vec())
+    }
+
+    #[tokio::test]
+    async fn accumulate_up_to_weight_full_group() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut batches = receiver.accumulate_up_to_weight::<Volume>(100);
+
+        sender.unbounded_send(Volume(60)).unwrap();
+        sender.unbounded_send(Volume(40)).unwrap();
+
+        assert_eq!(
+            futures::poll!(batches.next()),
+            Poll::Ready(Some(Volume(100)))
+        );
+
+        sender.unbounded_send(Volume(150)).unwrap();
+
+        assert_eq!(
+            futures::poll!(batches.next()),
+            Poll::Ready(Some(Volume(150)))
+        );
+        assert!(futures::poll!(batches.next()).is_pending());
+    }
+
+    #[tokio::test]
+    async fn accumulate_up_to_weight_max_items_boundary() {
+        let volumes = [1, 1, 1, 1, 1, 1, 1].map(Volume);
+
+        for (max_items, expected) in [(1, vec![1; 7]), (3, vec![3, 3, 1]), (7, vec![7])] {
+            let groups = stream::iter(volumes)
+                .accumulate_up_to_weight::<Volume>(100)
+                .max_items(max_items)
+                .map(|lhs| lhs.0)
+                .collect::<Vec<_>>()
+                .await;
+
+            assert_eq!(groups, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn accumulate_up_to_weight_max_items() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut batches = receiver.accumulate_up_to_weight::<Volume>(100).max_items(2);
+
+        sender.unbounded_send(Volume(10)).unwrap();
+        sender.unbounded_send(Volume(20)).unwrap();
+
+        assert_eq!(
+            futures::poll!(batches.next()),
+            Poll::Ready(Some(Volume(30)))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn accumulate_up_to_weight_max_group_age() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        tokio::spawn(async move {
+            while sender.unbounded_send(Volume(1)).is_ok() {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            }
+        });
+
+        let batches = receiver
+            .accumulate_up_to_weight::<Volume>(100)
+            .max_group_age_with(Duration::from_secs(1), TestTimer);
+
+        futures::pin_mut!(batches);
+
+        let start = Instant::now();
+
+        assert_eq!(batches.next().await, Some(Volume(4)));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    impl Splittable for Volume {
+        fn split_at(self, amount: usize) -> (Self, Self) {
+            (Volume(amount as u64), Volume(self.0 - amount as u64))
+        }
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_exact() {
+        let volumes = stream::iter([60, 30, 15, 40, 70, 80, 10, 5, 3, 2, 1, 1, 250].map(Volume));
+
+        let bars = volumes
+            .partially_accumulate_exact::<Volume>(100)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(bars, [100, 100, 100, 100, 100, 67].map(Volume).to_vec())
+    }
+
+    #[tokio::test]
+    async fn bin_pack() {
+        let volumes = stream::iter([50, 70, 20, 150, 30].map(Volume));
+
+        let packed = volumes
+            .bin_pack::<Volume>(100, Fit::Best)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(packed, [150, 80, 90].map(Volume).to_vec())
+    }
+
+    #[tokio::test]
+    async fn bin_pack_open_bins() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut packed = receiver.bin_pack::<Volume>(100, Fit::First).open_bins(2);
+
+        for volume in [60, 50, 30] {
+            sender.unbounded_send(Volume(volume)).unwrap();
+        }
+
+        assert!(futures::poll!(packed.next()).is_pending());
+
+        sender.unbounded_send(Volume(70)).unwrap();
+
+        assert_eq!(futures::poll!(packed.next()), Poll::Ready(Some(Volume(90))));
+
+        sender.unbounded_send(Volume(50)).unwrap();
+        drop(sender);
+
+        assert_eq!(
+            packed.collect::<Vec<_>>().await,
+            [100, 70].map(Volume).to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_accumulate_zero() {
+        let volumes = stream::iter([]);
+
+        assert_eq!(volumes.accumulate::<Volume>().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_accumulate_one() {
+        let volumes = stream::iter([Volume(10)]);
+
+        assert_eq!(volumes.accumulate::<Volume>().await.unwrap(), Volume(10));
+    }
+
+    #[tokio::test]
+    async fn test_accumulate() {
+        let volumes = stream::iter([Volume(10), Volume(15), Volume(20), Volume(25), Volume(30)]);
+
+        assert_eq!(volumes.accumulate::<Volume>().await.unwrap(), Volume(100));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum VolumeSize<const N: u64> {
+        Large(Volume),
+        Small(Volume),
+    }
+
+    impl<const N: u64> VolumeSize<N> {
+        pub fn new(volume: Volume) -> Self {
+            if volume.0 >= N {
+                Self::Large(volume)
+            } else {
+                Self::Small(volume)
+            }
+        }
+
+        pub fn volume_value(&self) -> Volume {
+            match self {
+                Self::Large(x) | Self::Small(x) => *x,
+            }
+        }
+    }
+
+    impl<const N: u64> Accumulable for VolumeSize<N> {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            *self = VolumeSize::new(self.volume_value().accumulate(&rhs.volume_value()))
+        }
+    }
+
+    impl<const N: u64> MaybeAccumulable for VolumeSize<N> {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            match self {
+                VolumeSize::Small(_) => {
+                    self.accumulate_from(rhs);
+
+                    true
+                }
+                _ => false,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_zero() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = stream::iter([]);
+
+        let partially_accumulated = volumes
+            .partially_accumulate::<VolumeSize100>()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(partially_accumulated, vec![])
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_one() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = stream::iter([VolumeSize100::new(Volume(60))]);
+
+        let partially_accumulated = volumes
+            .partially_accumulate::<VolumeSize100>()
+            .fuse()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            partially_accumulated,
+            vec![VolumeSize100::Small(Volume(60))]
+        )
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate() {
+        type VolumeSize100 = VolumeSize<100>;
 
         let volumes = stream::iter([
             VolumeSize100::new(Volume(60)),
@@ -376,4 +1947,551 @@ mod tests {
             ]
         )
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn partially_accumulate_timeout() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        let partially_accumulated = receiver.partially_accumulate_timeout_with::<VolumeSize100, _>(
+            Duration::from_secs(1),
+            TestTimer,
+        );
+
+        futures::pin_mut!(partially_accumulated);
+
+        let start = Instant::now();
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(60)))
+            .unwrap();
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(30)))
+            .unwrap();
+
+        assert_eq!(
+            partially_accumulated.next().await,
+            Some(VolumeSize100::Small(Volume(90)))
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(5)))
+            .unwrap();
+        drop(sender);
+
+        assert_eq!(
+            partially_accumulated.as_mut().collect::<Vec<_>>().await,
+            vec![VolumeSize100::Small(Volume(5))]
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn partially_accumulate_timeout_tokio() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        let partially_accumulated =
+            receiver.partially_accumulate_timeout::<VolumeSize100>(Duration::from_secs(1));
+
+        futures::pin_mut!(partially_accumulated);
+
+        let start = Instant::now();
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(60)))
+            .unwrap();
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(30)))
+            .unwrap();
+
+        assert_eq!(
+            partially_accumulated.next().await,
+            Some(VolumeSize100::Small(Volume(90)))
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn partially_accumulate_timeout_resets_on_item() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        let partially_accumulated = receiver.partially_accumulate_timeout_with::<VolumeSize100, _>(
+            Duration::from_secs(1),
+            TestTimer,
+        );
+
+        futures::pin_mut!(partially_accumulated);
+
+        let start = Instant::now();
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(10)))
+            .unwrap();
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        tokio::time::advance(Duration::from_millis(600)).await;
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(20)))
+            .unwrap();
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        tokio::time::advance(Duration::from_millis(600)).await;
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        assert_eq!(
+            partially_accumulated.next().await,
+            Some(VolumeSize100::Small(Volume(30)))
+        );
+        assert_eq!(start.elapsed(), Duration::from_millis(1600));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn partially_accumulate_max_group_age() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        tokio::spawn(async move {
+            while sender.unbounded_send(VolumeSize100::new(Volume(1))).is_ok() {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            }
+        });
+
+        let partially_accumulated = receiver
+            .partially_accumulate::<VolumeSize100>()
+            .max_group_age_with(Duration::from_secs(1), TestTimer);
+
+        futures::pin_mut!(partially_accumulated);
+
+        let start = Instant::now();
+
+        assert_eq!(
+            partially_accumulated.next().await,
+            Some(VolumeSize100::Small(Volume(4)))
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        assert_eq!(
+            partially_accumulated.next().await,
+            Some(VolumeSize100::Small(Volume(4)))
+        );
+        assert_eq!(start.elapsed(), Duration::from_millis(2200));
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_max_items_boundary() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [1, 1, 1, 1, 1, 1, 1].map(|volume| VolumeSize100::new(Volume(volume)));
+
+        for (max_items, expected) in [(1, vec![1; 7]), (3, vec![3, 3, 1]), (7, vec![7])] {
+            let groups = stream::iter(volumes)
+                .partially_accumulate::<VolumeSize100>()
+                .max_items(max_items)
+                .map(|lhs| lhs.volume_value().0)
+                .collect::<Vec<_>>()
+                .await;
+
+            assert_eq!(groups, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_max_merges() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut partially_accumulated = receiver
+            .partially_accumulate::<VolumeSize100>()
+            .max_merges(1);
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(1)))
+            .unwrap();
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(1)))
+            .unwrap();
+
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(VolumeSize100::Small(Volume(2))))
+        );
+    }
+
+    impl MaybeAccumulableLookahead for Volume {
+        fn maybe_accumulate_from_lookahead(&mut self, rhs: &Self, lookahead: &[Self]) -> bool {
+            if lookahead.iter().any(|volume| volume.0 >= 100) {
+                return false;
+            }
+
+            self.accumulate_from(rhs);
+
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_lookahead() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut partially_accumulated = receiver.partially_accumulate_lookahead::<Volume>(2);
+
+        for volume in [10, 20, 30] {
+            sender.unbounded_send(Volume(volume)).unwrap();
+        }
+
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        sender.unbounded_send(Volume(150)).unwrap();
+
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(Volume(10)))
+        );
+
+        sender.unbounded_send(Volume(5)).unwrap();
+        drop(sender);
+
+        assert_eq!(
+            partially_accumulated.collect::<Vec<_>>().await,
+            [20, 185].map(Volume).to_vec()
+        );
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Trade {
+        time: u64,
+        volume: Volume,
+    }
+
+    impl Timestamped for Trade {
+        type Timestamp = u64;
+
+        fn timestamp(&self) -> u64 {
+            self.time
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct TradeVolume(u64);
+
+    impl From<Trade> for TradeVolume {
+        fn from(trade: Trade) -> Self {
+            TradeVolume(trade.volume.0)
+        }
+    }
+
+    impl Accumulable<Trade> for TradeVolume {
+        fn accumulate_from(&mut self, rhs: &Trade) {
+            self.0 += rhs.volume.0;
+        }
+    }
+
+    impl Accumulable for TradeVolume {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 += rhs.0;
+        }
+    }
+
+    impl Deaccumulable for TradeVolume {
+        fn deaccumulate_from(&mut self, rhs: &Self) {
+            self.0 -= rhs.0;
+        }
+    }
+
+    impl Associative for TradeVolume {}
+
+    impl Commutative for TradeVolume {}
+
+    fn trade(time: u64, volume: u64) -> Trade {
+        Trade {
+            time,
+            volume: Volume(volume),
+        }
+    }
+
+    #[tokio::test]
+    async fn tumbling_window() {
+        let trades = [
+            trade(0, 10),
+            trade(10, 15),
+            trade(59, 20),
+            //
+            trade(60, 25),
+            //
+            trade(125, 30),
+            trade(130, 5),
+        ];
+
+        let windows = stream::iter(trades)
+            .tumbling_window::<TradeVolume>(60)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            windows,
+            vec![
+                (0, TradeVolume(45)),
+                (60, TradeVolume(25)),
+                (120, TradeVolume(35))
+            ]
+        )
+    }
+
+    #[test]
+    #[should_panic(expected = "window size must be positive")]
+    fn event_time_window_zero_size() {
+        drop(stream::iter([trade(0, 10)]).event_time_window::<TradeVolume>(0));
+    }
+
+    #[tokio::test]
+    async fn tumbling_window_fill_empty() {
+        let trades = [trade(30, 10), trade(200, 15)];
+
+        let windows = stream::iter(trades)
+            .tumbling_window::<TradeVolume>(60)
+            .fill_empty(TradeVolume(0))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            windows,
+            vec![
+                (0, TradeVolume(10)),
+                (60, TradeVolume(0)),
+                (120, TradeVolume(0)),
+                (180, TradeVolume(15))
+            ]
+        )
+    }
+
+    #[tokio::test]
+    async fn event_time_window() {
+        let trades = [
+            trade(5, 1),
+            trade(62, 2),
+            trade(55, 3),
+            trade(71, 4),
+            trade(58, 5),
+            trade(130, 6),
+            trade(20, 7),
+        ];
+
+        let windows = stream::iter(trades)
+            .event_time_window::<TradeVolume>(60)
+            .max_out_of_orderness(10)
+            .allowed_lateness(30)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            windows,
+            vec![
+                WindowEvent::Closed(0, TradeVolume(4)),
+                WindowEvent::Updated(0, TradeVolume(9)),
+                WindowEvent::Closed(60, TradeVolume(6)),
+                WindowEvent::Late(trade(20, 7)),
+                WindowEvent::Closed(120, TradeVolume(6)),
+            ]
+        )
+    }
+
+    #[tokio::test]
+    async fn event_time_window_closes_in_order() {
+        let trades = [trade(5, 1), trade(65, 2), trade(140, 3)];
+
+        let windows = stream::iter(trades)
+            .event_time_window::<TradeVolume>(60)
+            .max_out_of_orderness(10)
+            .allowed_lateness(30)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            windows,
+            vec![
+                WindowEvent::Closed(0, TradeVolume(1)),
+                WindowEvent::Closed(60, TradeVolume(2)),
+                WindowEvent::Closed(120, TradeVolume(3)),
+            ]
+        )
+    }
+
+    #[test]
+    fn event_time_window_late_new_window() {
+        let trades = [trade(130, 1), trade(10, 2), trade(20, 3)];
+
+        let mut windows = stream::iter(trades)
+            .chain(stream::pending())
+            .event_time_window::<TradeVolume>(60)
+            .allowed_lateness(120);
+
+        assert_eq!(
+            windows.next().now_or_never(),
+            Some(Some(WindowEvent::Closed(0, TradeVolume(2))))
+        );
+        assert_eq!(
+            windows.next().now_or_never(),
+            Some(Some(WindowEvent::Updated(0, TradeVolume(5))))
+        );
+        assert_eq!(windows.next().now_or_never(), None);
+    }
+
+    #[tokio::test]
+    async fn event_time_window_without_lateness() {
+        let trades = [trade(5, 1), trade(65, 2), trade(50, 3), trade(70, 4)];
+
+        let windows = stream::iter(trades)
+            .event_time_window::<TradeVolume>(60)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            windows,
+            vec![
+                WindowEvent::Closed(0, TradeVolume(1)),
+                WindowEvent::Late(trade(50, 3)),
+                WindowEvent::Closed(60, TradeVolume(6)),
+            ]
+        )
+    }
+
+    #[tokio::test]
+    async fn session_windows() {
+        let trades = [
+            trade(0, 10),
+            trade(5, 15),
+            trade(15, 20),
+            //
+            trade(30, 25),
+            trade(28, 5),
+            //
+            trade(50, 30),
+        ];
+
+        let sessions = stream::iter(trades)
+            .session_windows::<TradeVolume>(10)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            sessions,
+            vec![
+                (0, 15, TradeVolume(45)),
+                (28, 30, TradeVolume(30)),
+                (50, 50, TradeVolume(30))
+            ]
+        )
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn session_windows_idle_timeout() {
+        let (sender, receiver) = mpsc::unbounded();
+
+        let sessions = receiver
+            .session_windows::<TradeVolume>(10)
+            .idle_timeout_with(Duration::from_secs(1), TestTimer);
+
+        futures::pin_mut!(sessions);
+
+        let start = Instant::now();
+
+        sender.unbounded_send(trade(0, 10)).unwrap();
+        sender.unbounded_send(trade(5, 15)).unwrap();
+
+        assert_eq!(sessions.next().await, Some((0, 5, TradeVolume(25))));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+
+        sender.unbounded_send(trade(8, 20)).unwrap();
+        drop(sender);
+
+        assert_eq!(
+            sessions.as_mut().collect::<Vec<_>>().await,
+            vec![(8, 8, TradeVolume(20))]
+        );
+    }
+
+    #[tokio::test]
+    async fn sliding_time_window() {
+        let trades = [
+            trade(0, 1),
+            trade(5, 2),
+            trade(12, 3),
+            trade(25, 4),
+            trade(41, 5),
+        ];
+
+        let expected = vec![
+            (0, TradeVolume(10)),
+            (10, TradeVolume(7)),
+            (20, TradeVolume(9)),
+            (30, TradeVolume(5)),
+            (40, TradeVolume(5)),
+        ];
+
+        let recombined = stream::iter(trades)
+            .sliding_time_window::<TradeVolume>(30, 10)
+            .collect::<Vec<_>>()
+            .await;
+
+        let deaccumulated = stream::iter(trades)
+            .sliding_time_window::<TradeVolume>(30, 10)
+            .deaccumulate()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(recombined, expected);
+        assert_eq!(deaccumulated, expected);
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_flush_on_pending() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        let mut partially_accumulated = receiver
+            .partially_accumulate::<VolumeSize100>()
+            .flush_on_pending();
+
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(10)))
+            .unwrap();
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(20)))
+            .unwrap();
+
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(VolumeSize100::Small(Volume(30))))
+        );
+
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(120)))
+            .unwrap();
+        sender
+            .unbounded_send(VolumeSize100::new(Volume(5)))
+            .unwrap();
+
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(VolumeSize100::Large(Volume(120))))
+        );
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(VolumeSize100::Small(Volume(5))))
+        );
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+    }
 }
@@ -0,0 +1,286 @@
+//! Floating-point sums that carry a compensation term for the low-order bits
+//! lost by each addition.
+
+use crate::{Accumulable, Associative, Commutative, Deaccumulable};
+
+/// Kahan summation: the error of each addition is subtracted from the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KahanSum<F> {
+    sum: F,
+    compensation: F,
+}
+
+/// Neumaier's variant of [`KahanSum`], which also stays accurate when an
+/// added value is larger in magnitude than the running sum.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NeumaierSum<F> {
+    sum: F,
+    compensation: F,
+}
+
+macro_rules! impl_sums {
+    ($($f:ty),*) => {$(
+        impl KahanSum<$f> {
+            pub fn value(&self) -> $f {
+                self.sum - self.compensation
+            }
+        }
+
+        impl From<$f> for KahanSum<$f> {
+            fn from(value: $f) -> Self {
+                Self {
+                    sum: value,
+                    compensation: 0.0,
+                }
+            }
+        }
+
+        impl Accumulable<$f> for KahanSum<$f> {
+            fn accumulate_from(&mut self, rhs: &$f) {
+                let y = rhs - self.compensation;
+                let t = self.sum + y;
+
+                self.compensation = (t - self.sum) - y;
+                self.sum = t;
+            }
+        }
+
+        impl Accumulable for KahanSum<$f> {
+            fn accumulate_from(&mut self, rhs: &Self) {
+                self.accumulate_from(&rhs.sum);
+                self.accumulate_from(&-rhs.compensation);
+            }
+        }
+
+        impl Deaccumulable for KahanSum<$f> {
+            fn deaccumulate_from(&mut self, rhs: &Self) {
+                self.accumulate_from(&-rhs.sum);
+                self.accumulate_from(&rhs.compensation);
+            }
+        }
+
+        impl Associative for KahanSum<$f> {}
+
+        impl Commutative for KahanSum<$f> {}
+
+        impl NeumaierSum<$f> {
+            pub fn value(&self) -> $f {
+                self.sum + self.compensation
+            }
+        }
+
+        impl From<$f> for NeumaierSum<$f> {
+            fn from(value: $f) -> Self {
+                Self {
+                    sum: value,
+                    compensation: 0.0,
+                }
+            }
+        }
+
+        impl Accumulable<$f> for NeumaierSum<$f> {
+            fn accumulate_from(&mut self, rhs: &$f) {
+                let t = self.sum + rhs;
+
+                if self.sum.abs() >= rhs.abs() {
+                    self.compensation += (self.sum - t) + rhs;
+                } else {
+                    self.compensation += (rhs - t) + self.sum;
+                }
+
+                self.sum = t;
+            }
+        }
+
+        impl Accumulable for NeumaierSum<$f> {
+            fn accumulate_from(&mut self, rhs: &Self) {
+                self.accumulate_from(&rhs.sum);
+                self.compensation += rhs.compensation;
+            }
+        }
+
+        impl Deaccumulable for NeumaierSum<$f> {
+            fn deaccumulate_from(&mut self, rhs: &Self) {
+                self.accumulate_from(&-rhs.sum);
+                self.compensation -= rhs.compensation;
+            }
+        }
+
+        impl Associative for NeumaierSum<$f> {}
+
+        impl Commutative for NeumaierSum<$f> {}
+    )*};
+}
+
+impl_sums!(f32, f64);
+
+#[cfg(test)]
+mod tests {
+    use futures::stream::{self, TryStreamExt};
+
+    use crate::iter::{Accumulate, SlidingTimeWindow};
+    use crate::stream::Accumulate as _;
+    use crate::try_stream::TryPartiallyAccumulate;
+    use crate::{MaybeAccumulable, Timestamped};
+
+    use super::*;
+
+    fn naive(values: &[f64]) -> f64 {
+        values.iter().sum()
+    }
+
+    fn tiny_increments() -> Vec<f64> {
+        let mut values = vec![1.0];
+
+        values.extend(std::iter::repeat_n(1e-16, 10_000));
+
+        values
+    }
+
+    #[test]
+    fn kahan_tiny_increments() {
+        let values = tiny_increments();
+
+        let kahan = values
+            .iter()
+            .copied()
+            .accumulate::<KahanSum<f64>>()
+            .unwrap();
+
+        assert_eq!(naive(&values), 1.0);
+        assert!((kahan.value() - (1.0 + 1e-12)).abs() < 1e-15);
+    }
+
+    #[test]
+    fn neumaier_large_cancellation() {
+        let values = [1.0, 1e100, 1.0, -1e100];
+
+        let kahan = values.into_iter().accumulate::<KahanSum<f64>>().unwrap();
+        let neumaier = values.into_iter().accumulate::<NeumaierSum<f64>>().unwrap();
+
+        assert_eq!(naive(&values), 0.0);
+        assert_eq!(kahan.value(), 0.0);
+        assert_eq!(neumaier.value(), 2.0);
+    }
+
+    #[test]
+    fn neumaier_f32() {
+        let values = std::iter::once(1.0f32).chain(std::iter::repeat_n(1e-8, 1000));
+
+        let neumaier = values.accumulate::<NeumaierSum<f32>>().unwrap();
+
+        assert!((neumaier.value() - 1.00001).abs() < 1e-7);
+    }
+
+    #[test]
+    fn merge() {
+        let values = tiny_increments();
+        let (head, tail) = values.split_at(5000);
+
+        let kahan = head.iter().copied().accumulate::<KahanSum<f64>>().unwrap();
+        let neumaier = head
+            .iter()
+            .copied()
+            .accumulate::<NeumaierSum<f64>>()
+            .unwrap();
+
+        let kahan_tail = tail.iter().copied().accumulate::<KahanSum<f64>>();
+        let neumaier_tail = tail.iter().copied().accumulate::<NeumaierSum<f64>>();
+
+        let kahan = kahan.accumulate(&kahan_tail.unwrap());
+        let neumaier = neumaier.accumulate(&neumaier_tail.unwrap());
+
+        assert!((kahan.value() - (1.0 + 1e-12)).abs() < 1e-15);
+        assert!((neumaier.value() - (1.0 + 1e-12)).abs() < 1e-15);
+    }
+
+    #[tokio::test]
+    async fn stream_accumulate() {
+        let values = stream::iter([1.0, 1e100, 1.0, -1e100]);
+
+        let neumaier = values.accumulate::<NeumaierSum<f64>>().await.unwrap();
+
+        assert_eq!(neumaier.value(), 2.0);
+    }
+
+    /// Groups values as long as their sum stays at most 1.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct UpToOne(KahanSum<f64>);
+
+    impl From<f64> for UpToOne {
+        fn from(value: f64) -> Self {
+            UpToOne(KahanSum::from(value))
+        }
+    }
+
+    impl MaybeAccumulable<f64> for UpToOne {
+        fn maybe_accumulate_from(&mut self, rhs: &f64) -> bool {
+            let sum = self.0.accumulate(rhs);
+
+            if sum.value() > 1.0 {
+                return false;
+            }
+
+            self.0 = sum;
+
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn try_partially_accumulate() {
+        let values = stream::iter(std::iter::repeat_n(Ok::<_, ()>(0.1), 30));
+
+        let groups = values
+            .try_partially_accumulate::<UpToOne>()
+            .map_ok(|group| group.0.value())
+            .try_collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(naive(&[0.1; 10]), 0.9999999999999999);
+        assert_eq!(groups, Ok(vec![1.0; 3]));
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Tick {
+        time: u64,
+        value: f64,
+    }
+
+    impl Timestamped for Tick {
+        type Timestamp = u64;
+
+        fn timestamp(&self) -> u64 {
+            self.time
+        }
+    }
+
+    impl From<Tick> for NeumaierSum<f64> {
+        fn from(tick: Tick) -> Self {
+            NeumaierSum::from(tick.value)
+        }
+    }
+
+    impl Accumulable<Tick> for NeumaierSum<f64> {
+        fn accumulate_from(&mut self, rhs: &Tick) {
+            self.accumulate_from(&rhs.value);
+        }
+    }
+
+    #[test]
+    fn sliding_time_window_deaccumulate() {
+        let ticks = (0..1000).map(|time| Tick { time, value: 0.1 });
+
+        let windows = ticks
+            .sliding_time_window::<NeumaierSum<f64>>(10, 1)
+            .deaccumulate()
+            .filter(|(start, _)| (0..=990).contains(start))
+            .collect::<Vec<_>>();
+
+        assert_eq!(windows.len(), 991);
+
+        for (start, sum) in windows {
+            assert!((sum.value() - 1.0).abs() < 1e-15, "window at {start}");
+        }
+    }
+}
@@ -0,0 +1,505 @@
+//! Property checks for downstream `Accumulable` and `MaybeAccumulable` impls,
+//! driven by [`quickcheck`]. Each check panics with a counterexample when the
+//! law does not hold.
+//!
+//! Accumulators that can't implement [`Arbitrary`] themselves can be checked
+//! through [`Accumulated`], which builds them from arbitrary items:
+//!
+//! ```ignore
+//! use accumulable::testing::{self, Accumulated};
+//!
+//! #[test]
+//! fn bar_laws() {
+//!     testing::check_commutative_laws::<Accumulated<Bar, Trade>>();
+//!     testing::check_identity::<Accumulated<Bar, Trade>>();
+//!     testing::check_accumulate_consistency::<Accumulated<Bar, Trade>, Trade>();
+//! }
+//! ```
+//!
+//! The checks compare with `==`, so floating point accumulators, whose laws
+//! only hold up to rounding, need a `PartialEq` with some tolerance.
+
+use std::fmt::Debug;
+
+use quickcheck::{QuickCheck, TestResult};
+
+pub use quickcheck::{Arbitrary, Gen};
+
+use crate::{Accumulable, Associative, Commutative, MaybeAccumulable};
+
+const TESTS: u64 = 100;
+
+fn quickcheck() -> QuickCheck {
+    QuickCheck::new().tests(TESTS)
+}
+
+/// A `T` built by accumulating a non-empty run of arbitrary `Rhs` items.
+///
+/// Shrinks by shrinking the run, and accumulates like `T`, so it is
+/// [`Associative`] or [`Commutative`] whenever `T` is.
+#[derive(Clone, Debug)]
+pub struct Accumulated<T, Rhs> {
+    lhs: T,
+    items: Vec<Rhs>,
+}
+
+impl<T, Rhs> Accumulated<T, Rhs>
+where
+    T: From<Rhs> + Accumulable<Rhs>,
+    Rhs: Clone,
+{
+    fn from_items(items: Vec<Rhs>) -> Self {
+        let (first, rest) = items.split_first().expect("items must not be empty");
+
+        let lhs = rest
+            .iter()
+            .fold(T::from(first.clone()), |lhs, rhs| lhs.accumulate(rhs));
+
+        Self { lhs, items }
+    }
+}
+
+impl<T, Rhs> Accumulated<T, Rhs> {
+    pub fn get(&self) -> &T {
+        &self.lhs
+    }
+
+    pub fn into_inner(self) -> T {
+        self.lhs
+    }
+}
+
+impl<T, Rhs> PartialEq for Accumulated<T, Rhs>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.lhs == other.lhs
+    }
+}
+
+impl<T, Rhs> Default for Accumulated<T, Rhs>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self {
+            lhs: T::default(),
+            items: Vec::new(),
+        }
+    }
+}
+
+impl<T, Rhs> Arbitrary for Accumulated<T, Rhs>
+where
+    T: From<Rhs> + Accumulable<Rhs> + Clone + 'static,
+    Rhs: Arbitrary,
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        let mut items = vec![Rhs::arbitrary(g)];
+
+        items.extend(Vec::<Rhs>::arbitrary(g));
+
+        Self::from_items(items)
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new(
+            self.items
+                .shrink()
+                .filter(|items| !items.is_empty())
+                .map(Self::from_items),
+        )
+    }
+}
+
+impl<T, Rhs> From<Rhs> for Accumulated<T, Rhs>
+where
+    T: From<Rhs>,
+    Rhs: Clone,
+{
+    fn from(rhs: Rhs) -> Self {
+        Self {
+            lhs: T::from(rhs.clone()),
+            items: vec![rhs],
+        }
+    }
+}
+
+impl<T, Rhs> Accumulable<Rhs> for Accumulated<T, Rhs>
+where
+    T: Accumulable<Rhs>,
+    Rhs: Clone,
+{
+    fn accumulate_from(&mut self, rhs: &Rhs) {
+        self.lhs.accumulate_from(rhs);
+        self.items.push(rhs.clone());
+    }
+}
+
+impl<T, Rhs> Accumulable for Accumulated<T, Rhs>
+where
+    T: Accumulable,
+    Rhs: Clone,
+{
+    fn accumulate_from(&mut self, rhs: &Self) {
+        self.lhs.accumulate_from(&rhs.lhs);
+        self.items.extend_from_slice(&rhs.items);
+    }
+}
+
+impl<T, Rhs> Associative for Accumulated<T, Rhs>
+where
+    T: Associative,
+    Rhs: Clone,
+{
+}
+
+impl<T, Rhs> Commutative for Accumulated<T, Rhs>
+where
+    T: Commutative,
+    Rhs: Clone,
+{
+}
+
+/// Checks what the [`Associative`] marker claims: associativity, and
+/// [`check_accumulate_consistency`] of `T` with itself.
+///
+/// The marker must already be implemented; to justify it before adding it,
+/// run [`check_associative`], which only needs [`Accumulable`].
+pub fn check_associative_laws<T>()
+where
+    T: Associative + Arbitrary + PartialEq + Debug,
+{
+    check_associative::<T>();
+    check_accumulate_consistency::<T, T>();
+}
+
+/// Checks what the [`Commutative`] marker claims: commutativity, and
+/// everything [`check_associative_laws`] checks.
+pub fn check_commutative_laws<T>()
+where
+    T: Commutative + Arbitrary + PartialEq + Debug,
+{
+    check_associative_laws::<T>();
+    check_commutative::<T>();
+}
+
+/// Checks the laws every [`MaybeAccumulable`] impl must satisfy:
+/// [`check_maybe_no_mutation`] and agreement between `maybe_accumulate` and
+/// `maybe_accumulate_from`.
+pub fn check_maybe_laws<T>()
+where
+    T: MaybeAccumulable + Arbitrary + PartialEq + Debug,
+{
+    check_maybe_no_mutation::<T>();
+
+    fn prop<T>(lhs: T, rhs: T) -> TestResult
+    where
+        T: MaybeAccumulable + Clone + PartialEq,
+    {
+        let mut from = lhs.clone();
+        let accumulated = from.maybe_accumulate_from(&rhs);
+
+        let result = lhs.maybe_accumulate(&rhs);
+
+        TestResult::from_bool(result.is_ok() == accumulated && result.unwrap_or_else(|x| x) == from)
+    }
+
+    quickcheck().quickcheck(prop::<T> as fn(T, T) -> TestResult);
+}
+
+/// `(a + b) + c == a + (b + c)`, whether or not `T` is marked [`Associative`].
+pub fn check_associative<T>()
+where
+    T: Accumulable + Arbitrary + PartialEq + Debug,
+{
+    fn prop<T>(a: T, b: T, c: T) -> bool
+    where
+        T: Accumulable + Clone + PartialEq,
+    {
+        a.clone().accumulate(&b).accumulate(&c) == a.accumulate(&b.accumulate(&c))
+    }
+
+    quickcheck().quickcheck(prop::<T> as fn(T, T, T) -> bool);
+}
+
+/// `a + b == b + a`, whether or not `T` is marked [`Commutative`].
+pub fn check_commutative<T>()
+where
+    T: Accumulable + Arbitrary + PartialEq + Debug,
+{
+    fn prop<T>(a: T, b: T) -> bool
+    where
+        T: Accumulable + Clone + PartialEq,
+    {
+        a.clone().accumulate(&b) == b.accumulate(&a)
+    }
+
+    quickcheck().quickcheck(prop::<T> as fn(T, T) -> bool);
+}
+
+/// `T::default() + a == a == a + T::default()`.
+pub fn check_identity<T>()
+where
+    T: Accumulable + Default + Arbitrary + PartialEq + Debug,
+{
+    fn prop<T>(a: T) -> bool
+    where
+        T: Accumulable + Default + Clone + PartialEq,
+    {
+        T::default().accumulate(&a) == a && a.clone().accumulate(&T::default()) == a
+    }
+
+    quickcheck().quickcheck(prop::<T> as fn(T) -> bool);
+}
+
+/// Accumulating an `Rhs` item agrees with accumulating it converted into a
+/// `T`, as adaptors do when they merge partial results, and `accumulate`
+/// agrees with `accumulate_from`.
+pub fn check_accumulate_consistency<T, Rhs>()
+where
+    T: From<Rhs> + Accumulable<Rhs> + Accumulable + Arbitrary + PartialEq + Debug,
+    Rhs: Arbitrary + Debug,
+{
+    fn prop<T, Rhs>(lhs: T, rhs: Rhs) -> bool
+    where
+        T: From<Rhs> + Accumulable<Rhs> + Accumulable + Clone + PartialEq,
+        Rhs: Clone,
+    {
+        let mut from = lhs.clone();
+
+        Accumulable::<Rhs>::accumulate_from(&mut from, &rhs);
+
+        let converted = Accumulable::<T>::accumulate(lhs.clone(), &T::from(rhs.clone()));
+
+        Accumulable::<Rhs>::accumulate(lhs, &rhs) == from && from == converted
+    }
+
+    quickcheck().quickcheck(prop::<T, Rhs> as fn(T, Rhs) -> bool);
+}
+
+/// A `maybe_accumulate_from` returning `false` leaves `self` untouched.
+pub fn check_maybe_no_mutation<T>()
+where
+    T: MaybeAccumulable + Arbitrary + PartialEq + Debug,
+{
+    fn prop<T>(lhs: T, rhs: T) -> bool
+    where
+        T: MaybeAccumulable + Clone + PartialEq,
+    {
+        let mut after = lhs.clone();
+
+        after.maybe_accumulate_from(&rhs) || after == lhs
+    }
+
+    quickcheck().quickcheck(prop::<T> as fn(T, T) -> bool);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Volume(u8);
+
+    impl Arbitrary for Volume {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Volume(u8::arbitrary(g))
+        }
+    }
+
+    impl MaybeAccumulable for Volume {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            let accumulated = self.0.checked_add(rhs.0);
+
+            if let Some(accumulated) = accumulated {
+                self.0 = accumulated;
+            }
+
+            accumulated.is_some()
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Midpoint(u8);
+
+    impl Arbitrary for Midpoint {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Midpoint(u8::arbitrary(g))
+        }
+    }
+
+    impl Accumulable for Midpoint {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 = ((u16::from(self.0) + u16::from(rhs.0)) / 2) as u8;
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub struct Total(u8);
+
+    impl Arbitrary for Total {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Total(u8::arbitrary(g))
+        }
+    }
+
+    impl Accumulable for Total {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 = self.0.wrapping_add(rhs.0);
+        }
+    }
+
+    impl Associative for Total {}
+
+    impl Commutative for Total {}
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Max(u8);
+
+    impl Arbitrary for Max {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Max(u8::arbitrary(g))
+        }
+    }
+
+    impl Accumulable for Max {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 = self.0.max(rhs.0);
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Greedy(u8);
+
+    impl Arbitrary for Greedy {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Greedy(u8::arbitrary(g))
+        }
+    }
+
+    impl MaybeAccumulable for Greedy {
+        fn maybe_accumulate_from(&mut self, rhs: &Self) -> bool {
+            self.0 = self.0.saturating_add(rhs.0);
+
+            self.0 < u8::MAX
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Trade {
+        volume: u8,
+        price: u8,
+    }
+
+    impl Arbitrary for Trade {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Trade {
+                volume: u8::arbitrary(g),
+                price: u8::arbitrary(g),
+            }
+        }
+    }
+
+    /// Volume and last price; only associative, as the last price depends on
+    /// the order.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Bar {
+        volume: u8,
+        last: u8,
+    }
+
+    impl From<Trade> for Bar {
+        fn from(trade: Trade) -> Self {
+            Bar {
+                volume: trade.volume,
+                last: trade.price,
+            }
+        }
+    }
+
+    impl Accumulable<Trade> for Bar {
+        fn accumulate_from(&mut self, rhs: &Trade) {
+            self.accumulate_from(&Bar::from(*rhs));
+        }
+    }
+
+    impl Accumulable for Bar {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.volume = self.volume.wrapping_add(rhs.volume);
+            self.last = rhs.last;
+        }
+    }
+
+    impl Associative for Bar {}
+
+    /// Forgets to count the volume of single trades.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Inconsistent(Bar);
+
+    impl From<Trade> for Inconsistent {
+        fn from(trade: Trade) -> Self {
+            Inconsistent(Bar::from(trade))
+        }
+    }
+
+    impl Accumulable<Trade> for Inconsistent {
+        fn accumulate_from(&mut self, rhs: &Trade) {
+            self.0.last = rhs.price;
+        }
+    }
+
+    impl Accumulable for Inconsistent {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0.accumulate_from(&rhs.0);
+        }
+    }
+
+    #[test]
+    fn laws() {
+        check_commutative_laws::<Total>();
+        check_identity::<Total>();
+    }
+
+    #[test]
+    fn accumulated_laws() {
+        check_associative_laws::<Accumulated<Bar, Trade>>();
+        check_accumulate_consistency::<Accumulated<Bar, Trade>, Trade>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn bar_is_not_commutative() {
+        check_commutative::<Accumulated<Bar, Trade>>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn inconsistent_item_accumulation() {
+        check_accumulate_consistency::<Accumulated<Inconsistent, Trade>, Trade>();
+    }
+
+    #[test]
+    fn laws_before_markers() {
+        check_associative::<Max>();
+        check_commutative::<Max>();
+    }
+
+    #[test]
+    fn maybe_laws() {
+        check_maybe_laws::<Volume>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn midpoint_is_not_associative() {
+        check_associative::<Midpoint>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn rejection_mutates() {
+        check_maybe_no_mutation::<Greedy>();
+    }
+}
@@ -0,0 +1,253 @@
+use std::pin::Pin;
+use std::task::Context;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::future::{self, Future};
+
+use crate::Timestamped;
+
+pub trait Timer {
+    type Delay: Future<Output = ()>;
+
+    fn delay(&self, duration: Duration) -> Self::Delay;
+}
+
+/// Timer of adaptors without any time limit configured; its delays never fire.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Never;
+
+impl Timer for Never {
+    type Delay = future::Pending<()>;
+
+    fn delay(&self, _duration: Duration) -> Self::Delay {
+        future::pending()
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl Timer for TokioTimer {
+    type Delay = tokio::time::Sleep;
+
+    fn delay(&self, duration: Duration) -> Self::Delay {
+        tokio::time::sleep(duration)
+    }
+}
+
+pub(crate) fn reset<T>(mut delay: Pin<&mut Option<T::Delay>>, timer: &T, duration: Option<Duration>)
+where
+    T: Timer,
+{
+    delay.set(duration.map(|duration| timer.delay(duration)));
+}
+
+pub(crate) fn poll_elapsed<D>(delay: Pin<&mut Option<D>>, cx: &mut Context<'_>) -> bool
+where
+    D: Future<Output = ()>,
+{
+    match delay.as_pin_mut() {
+        Some(delay) => delay.poll(cx).is_ready(),
+        None => false,
+    }
+}
+
+pub type TimestampOf<R> = <R as Timestamped>::Timestamp;
+
+pub type SpanOf<R> = <TimestampOf<R> as Timestamp>::Span;
+
+/// Event time, as returned by [`Timestamped::timestamp`].
+pub trait Timestamp: Copy + Ord {
+    /// Length of time between two timestamps, `Default` being zero.
+    type Span: Copy + Ord + Default;
+
+    /// Start of the `span` wide window containing `self`, aligned to zero.
+    ///
+    /// Like `add_span` and `sub_span`, saturates instead of overflowing.
+    fn floor(self, span: Self::Span) -> Self;
+
+    fn add_span(self, span: Self::Span) -> Self;
+
+    fn sub_span(self, span: Self::Span) -> Self;
+
+    /// Time elapsed since `earlier`, or zero if `earlier` is later.
+    fn since(self, earlier: Self) -> Self::Span;
+}
+
+macro_rules! impl_unsigned_timestamp {
+    ($($ty:ty),*) => {$(
+        impl Timestamp for $ty {
+            type Span = $ty;
+
+            fn floor(self, span: Self::Span) -> Self {
+                self - self % span
+            }
+
+            fn add_span(self, span: Self::Span) -> Self {
+                self.saturating_add(span)
+            }
+
+            fn sub_span(self, span: Self::Span) -> Self {
+                self.saturating_sub(span)
+            }
+
+            fn since(self, earlier: Self) -> Self::Span {
+                self.saturating_sub(earlier)
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_signed_timestamp {
+    ($($ty:ty),*) => {$(
+        impl Timestamp for $ty {
+            type Span = $ty;
+
+            fn floor(self, span: Self::Span) -> Self {
+                self.checked_sub(self.rem_euclid(span)).unwrap_or(<$ty>::MIN)
+            }
+
+            fn add_span(self, span: Self::Span) -> Self {
+                self.saturating_add(span)
+            }
+
+            fn sub_span(self, span: Self::Span) -> Self {
+                self.saturating_sub(span)
+            }
+
+            fn since(self, earlier: Self) -> Self::Span {
+                self.saturating_sub(earlier).max(0)
+            }
+        }
+    )*};
+}
+
+impl_unsigned_timestamp!(u32, u64, u128, usize);
+impl_signed_timestamp!(i32, i64, i128, isize);
+
+impl Timestamp for Duration {
+    type Span = Duration;
+
+    fn floor(self, span: Self::Span) -> Self {
+        let rem = self.as_nanos() % span.as_nanos();
+
+        self - Duration::from_nanos(rem as u64)
+    }
+
+    fn add_span(self, span: Self::Span) -> Self {
+        self.saturating_add(span)
+    }
+
+    fn sub_span(self, span: Self::Span) -> Self {
+        self.saturating_sub(span)
+    }
+
+    fn since(self, earlier: Self) -> Self::Span {
+        self.saturating_sub(earlier)
+    }
+}
+
+impl Timestamp for SystemTime {
+    type Span = Duration;
+
+    fn floor(self, span: Self::Span) -> Self {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => UNIX_EPOCH + after.floor(span),
+            Err(err) => {
+                let rem = err.duration().as_nanos() % span.as_nanos();
+
+                if rem == 0 {
+                    self
+                } else {
+                    self.sub_span(span - Duration::from_nanos(rem as u64))
+                }
+            }
+        }
+    }
+
+    fn add_span(self, span: Self::Span) -> Self {
+        saturate(self, span, SystemTime::checked_add)
+    }
+
+    fn sub_span(self, span: Self::Span) -> Self {
+        saturate(self, span, SystemTime::checked_sub)
+    }
+
+    fn since(self, earlier: Self) -> Self::Span {
+        self.duration_since(earlier).unwrap_or_default()
+    }
+}
+
+/// Steps `time` by `span`, or as far as the platform can represent: unlike
+/// the integers, `SystemTime` has no bound to saturate to.
+fn saturate(
+    mut time: SystemTime,
+    mut span: Duration,
+    step: impl Fn(&SystemTime, Duration) -> Option<SystemTime>,
+) -> SystemTime {
+    let mut chunk = span;
+
+    while !span.is_zero() {
+        match step(&time, chunk) {
+            Some(next) => {
+                time = next;
+                span -= chunk;
+                chunk = chunk.min(span);
+            }
+            None if chunk <= Duration::from_nanos(1) => break,
+            None => chunk /= 2,
+        }
+    }
+
+    time
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Tokio's timer, available to tests built without the `tokio` feature.
+    #[derive(Clone, Copy, Debug)]
+    pub struct TestTimer;
+
+    impl Timer for TestTimer {
+        type Delay = tokio::time::Sleep;
+
+        fn delay(&self, duration: Duration) -> Self::Delay {
+            tokio::time::sleep(duration)
+        }
+    }
+
+    #[test]
+    fn floor_saturates() {
+        assert_eq!(i64::MIN.floor(10), i64::MIN);
+        assert_eq!((i64::MIN + 1).floor(10), i64::MIN);
+        assert_eq!((-15i64).floor(10), -20);
+        assert_eq!(15u64.floor(10), 10);
+    }
+
+    #[test]
+    fn system_time_saturates() {
+        let max = UNIX_EPOCH.add_span(Duration::MAX);
+        let min = UNIX_EPOCH.sub_span(Duration::MAX);
+
+        assert!(max > UNIX_EPOCH);
+        assert!(min < UNIX_EPOCH);
+        assert_eq!(max.add_span(Duration::from_secs(1)), max);
+        assert_eq!(min.sub_span(Duration::from_secs(1)), min);
+        assert!(min.floor(Duration::from_secs(60)) <= min);
+
+        let time = UNIX_EPOCH + Duration::from_secs(90);
+
+        assert_eq!(
+            time.add_span(Duration::from_secs(30)),
+            UNIX_EPOCH + Duration::from_secs(120)
+        );
+        assert_eq!(
+            time.sub_span(Duration::from_secs(30)),
+            UNIX_EPOCH + Duration::from_secs(60)
+        );
+    }
+}
@@ -1,13 +1,18 @@
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use pin_project::pin_project;
 
 use futures::ready;
 use futures::stream::{FusedStream, Stream};
 
+use crate::iter::Exact;
 use crate::stream::AccumulatedState;
-use crate::MaybeAccumulable;
+#[cfg(feature = "tokio")]
+use crate::time::TokioTimer;
+use crate::time::{self, Never, Timer};
+use crate::{Accumulable, MaybeAccumulable, Splittable, Weighable};
 
 pub trait TryPartiallyAccumulate<Rhs> {
     fn try_partially_accumulate<Lhs>(self) -> TryPartiallyAccumulated<Self, Lhs>
@@ -31,10 +36,17 @@ where
 }
 
 #[pin_project]
-pub struct TryPartiallyAccumulated<S, Lhs> {
+pub struct TryPartiallyAccumulated<S, Lhs, T: Timer = Never> {
     #[pin]
     stream: S,
     lhs: AccumulatedState<Lhs>,
+    items: usize,
+    max_items: usize,
+    timer: T,
+    max_age: Option<Duration>,
+    flush_on_pending: bool,
+    #[pin]
+    deadline: Option<T::Delay>,
 }
 
 impl<S, Lhs> TryPartiallyAccumulated<S, Lhs> {
@@ -42,24 +54,97 @@ impl<S, Lhs> TryPartiallyAccumulated<S, Lhs> {
         Self {
             stream,
             lhs: AccumulatedState::Uninit,
+            items: 0,
+            max_items: usize::MAX,
+            timer: Never,
+            max_age: None,
+            flush_on_pending: false,
+            deadline: None,
         }
     }
 }
 
-impl<S, Lhs, V, E> FusedStream for TryPartiallyAccumulated<S, Lhs>
+impl<S, Lhs, T> TryPartiallyAccumulated<S, Lhs, T>
+where
+    T: Timer,
+{
+    /// Emits the open group as soon as upstream has no item ready, merging only
+    /// what is immediately available.
+    pub fn flush_on_pending(mut self) -> Self {
+        self.flush_on_pending = true;
+
+        self
+    }
+
+    /// Closes a group once it holds `max_items` items, whatever
+    /// `maybe_accumulate_from` would decide.
+    pub fn max_items(mut self, max_items: usize) -> Self {
+        assert!(max_items > 0, "max_items must be positive");
+
+        self.max_items = max_items;
+
+        self
+    }
+
+    /// Closes a group once `max_merges` items have been merged into its first.
+    pub fn max_merges(self, max_merges: usize) -> Self {
+        self.max_items(max_merges.saturating_add(1))
+    }
+
+    /// Emits the open group once `duration` has passed since its first item,
+    /// even if items keep merging into it.
+    #[cfg(feature = "tokio")]
+    pub fn max_group_age(self, duration: Duration) -> TryPartiallyAccumulated<S, Lhs, TokioTimer> {
+        self.max_group_age_with(duration, TokioTimer)
+    }
+
+    pub fn max_group_age_with<U>(
+        self,
+        duration: Duration,
+        timer: U,
+    ) -> TryPartiallyAccumulated<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        TryPartiallyAccumulated {
+            max_age: Some(duration),
+            ..self.with_timer(timer)
+        }
+    }
+
+    fn with_timer<U>(self, timer: U) -> TryPartiallyAccumulated<S, Lhs, U>
+    where
+        U: Timer,
+    {
+        TryPartiallyAccumulated {
+            stream: self.stream,
+            lhs: self.lhs,
+            items: self.items,
+            max_items: self.max_items,
+            timer,
+            max_age: self.max_age,
+            flush_on_pending: self.flush_on_pending,
+            deadline: None,
+        }
+    }
+}
+
+impl<S, Lhs, T, V, E> FusedStream for TryPartiallyAccumulated<S, Lhs, T>
 where
     S: Stream<Item = Result<V, E>>,
     Lhs: From<V> + MaybeAccumulable<V>,
+    T: Timer,
 {
     fn is_terminated(&self) -> bool {
         self.lhs.is_consumed()
     }
 }
 
-impl<S, Lhs, V, E> Stream for TryPartiallyAccumulated<S, Lhs>
+impl<S, Lhs, T, V, E> Stream for TryPartiallyAccumulated<S, Lhs, T>
 where
     S: Stream<Item = Result<V, E>>,
     Lhs: From<V> + MaybeAccumulable<V>,
+    T: Timer,
 {
     type Item = Result<Lhs, E>;
 
@@ -77,6 +162,9 @@ where
                         Some(first) => match first {
                             Ok(first) => {
                                 *proj.lhs = AccumulatedState::Accumulable(Lhs::from(first));
+                                *proj.items = 1;
+
+                                time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
                             }
                             Err(err) => break Some(Err(err)),
                         },
@@ -84,15 +172,43 @@ where
                     }
                 }
                 S::Accumulable(inner) => {
-                    let item = ready!(proj.stream.as_mut().poll_next(cx));
+                    if *proj.items >= *proj.max_items {
+                        proj.deadline.set(None);
+
+                        break Ok(proj.lhs.reinit()).transpose();
+                    }
+
+                    if proj.max_age.is_some() && proj.deadline.is_none() {
+                        time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
+                    }
+
+                    if time::poll_elapsed(proj.deadline.as_mut(), cx) {
+                        proj.deadline.set(None);
+
+                        break Ok(proj.lhs.reinit()).transpose();
+                    }
+
+                    let item = match proj.stream.as_mut().poll_next(cx) {
+                        Poll::Ready(item) => item,
+                        Poll::Pending if *proj.flush_on_pending => {
+                            proj.deadline.set(None);
+
+                            break Ok(proj.lhs.reinit()).transpose();
+                        }
+                        Poll::Pending => return Poll::Pending,
+                    };
 
                     match item {
                         Some(item) => match item {
                             Ok(item) => {
                                 if inner.maybe_accumulate_from(&item) {
+                                    *proj.items += 1;
+
                                     continue;
                                 } else {
-                                    drop(inner);
+                                    *proj.items = 1;
+
+                                    time::reset(proj.deadline.as_mut(), proj.timer, *proj.max_age);
 
                                     break Ok(proj.lhs.reaccumulable(Lhs::from(item))).transpose();
                                 }
@@ -100,7 +216,7 @@ where
                             Err(err) => break Some(Err(err)),
                         },
                         None => {
-                            drop(inner);
+                            proj.deadline.set(None);
 
                             break Ok(proj.lhs.reinit()).transpose();
                         }
@@ -114,11 +230,108 @@ where
     }
 }
 
+pub trait TryPartiallyAccumulateExact<Rhs> {
+    fn try_partially_accumulate_exact<Lhs>(
+        self,
+        capacity: usize,
+    ) -> TryPartiallyAccumulatedExact<Self, Lhs, Rhs>
+    where
+        Self: Sized,
+        Rhs: Weighable + Splittable,
+        Lhs: From<Rhs> + Accumulable<Rhs>;
+}
+
+impl<S, Rhs, E> TryPartiallyAccumulateExact<Rhs> for S
+where
+    S: Stream<Item = Result<Rhs, E>>,
+{
+    #[inline]
+    fn try_partially_accumulate_exact<Lhs>(
+        self,
+        capacity: usize,
+    ) -> TryPartiallyAccumulatedExact<S, Lhs, Rhs>
+    where
+        S: Sized,
+        Rhs: Weighable + Splittable,
+        Lhs: From<Rhs> + Accumulable<Rhs>,
+    {
+        TryPartiallyAccumulatedExact::new(self, capacity)
+    }
+}
+
+/// Groups items into exactly `capacity` of weight each, splitting an item that
+/// overflows a group and carrying the remainder forward. Errors are passed
+/// through without closing the open group.
+#[pin_project]
+pub struct TryPartiallyAccumulatedExact<S, Lhs, Rhs> {
+    #[pin]
+    stream: S,
+    exact: Exact<Rhs, Lhs>,
+    terminated: bool,
+}
+
+impl<S, Lhs, Rhs> TryPartiallyAccumulatedExact<S, Lhs, Rhs>
+where
+    Rhs: Weighable + Splittable,
+    Lhs: From<Rhs> + Accumulable<Rhs>,
+{
+    pub fn new(stream: S, capacity: usize) -> Self {
+        Self {
+            stream,
+            exact: Exact::new(capacity),
+            terminated: false,
+        }
+    }
+}
+
+impl<S, Lhs, V, E> FusedStream for TryPartiallyAccumulatedExact<S, Lhs, V>
+where
+    S: Stream<Item = Result<V, E>>,
+    V: Weighable + Splittable,
+    Lhs: From<V> + Accumulable<V>,
+{
+    fn is_terminated(&self) -> bool {
+        self.terminated && self.exact.is_empty()
+    }
+}
+
+impl<S, Lhs, V, E> Stream for TryPartiallyAccumulatedExact<S, Lhs, V>
+where
+    S: Stream<Item = Result<V, E>>,
+    V: Weighable + Splittable,
+    Lhs: From<V> + Accumulable<V>,
+{
+    type Item = Result<Lhs, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut proj = self.project();
+
+        loop {
+            if let Some(lhs) = proj.exact.pop_full() {
+                return Poll::Ready(Some(Ok(lhs)));
+            }
+
+            if *proj.terminated {
+                return Poll::Ready(proj.exact.flush().map(Ok));
+            }
+
+            match ready!(proj.stream.as_mut().poll_next(cx)) {
+                Some(Ok(rhs)) => proj.exact.push(rhs),
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => *proj.terminated = true,
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use futures::stream::{self, TryStreamExt};
+    use futures::channel::mpsc;
+    use futures::stream::{self, StreamExt, TryStreamExt};
 
-    use crate::Accumulable;
+    use tokio::time::Instant;
+
+    use crate::time::tests::TestTimer;
 
     use super::*;
 
@@ -237,4 +450,116 @@ mod tests {
             ])
         )
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn partially_accumulate_max_group_age() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded();
+
+        tokio::spawn(async move {
+            while sender
+                .unbounded_send(Ok::<_, ()>(VolumeSize100::new(Volume(1))))
+                .is_ok()
+            {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            }
+        });
+
+        let partially_accumulated = receiver
+            .try_partially_accumulate::<VolumeSize100>()
+            .max_group_age_with(Duration::from_secs(1), TestTimer);
+
+        futures::pin_mut!(partially_accumulated);
+
+        let start = Instant::now();
+
+        assert_eq!(
+            partially_accumulated.next().await,
+            Some(Ok(VolumeSize100::Small(Volume(4))))
+        );
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_max_items() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let volumes = [1, 1, 1, 1, 1, 1, 1].map(|volume| VolumeSize100::new(Volume(volume)));
+
+        for (max_items, expected) in [(1, vec![1; 7]), (3, vec![3, 3, 1]), (7, vec![7])] {
+            let groups = stream::iter(volumes.map(Ok::<_, ()>))
+                .try_partially_accumulate::<VolumeSize100>()
+                .max_items(max_items)
+                .map_ok(|lhs| lhs.volume_value().0)
+                .try_collect::<Vec<_>>()
+                .await;
+
+            assert_eq!(groups, Ok(expected));
+        }
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_flush_on_pending() {
+        type VolumeSize100 = VolumeSize<100>;
+
+        let (sender, receiver) = mpsc::unbounded::<Result<_, ()>>();
+
+        let mut partially_accumulated = receiver
+            .try_partially_accumulate::<VolumeSize100>()
+            .flush_on_pending();
+
+        sender
+            .unbounded_send(Ok(VolumeSize100::new(Volume(10))))
+            .unwrap();
+        sender
+            .unbounded_send(Ok(VolumeSize100::new(Volume(20))))
+            .unwrap();
+
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(Ok(VolumeSize100::Small(Volume(30)))))
+        );
+        assert!(futures::poll!(partially_accumulated.next()).is_pending());
+
+        sender.unbounded_send(Err(())).unwrap();
+
+        assert_eq!(
+            futures::poll!(partially_accumulated.next()),
+            Poll::Ready(Some(Err(())))
+        );
+    }
+
+    impl Weighable for Volume {
+        fn weight(&self) -> usize {
+            self.0 as usize
+        }
+    }
+
+    impl Splittable for Volume {
+        fn split_at(self, amount: usize) -> (Self, Self) {
+            (Volume(amount as u64), Volume(self.0 - amount as u64))
+        }
+    }
+
+    #[tokio::test]
+    async fn partially_accumulate_exact() {
+        let volumes = stream::iter([Ok(Volume(60)), Ok(Volume(70)), Err(()), Ok(Volume(250))]);
+
+        let bars = volumes
+            .try_partially_accumulate_exact::<Volume>(100)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(
+            bars,
+            vec![
+                Ok(Volume(100)),
+                Err(()),
+                Ok(Volume(100)),
+                Ok(Volume(100)),
+                Ok(Volume(80))
+            ]
+        )
+    }
 }
@@ -0,0 +1,267 @@
+use std::collections::VecDeque;
+
+use crate::time::{SpanOf, Timestamp, TimestampOf};
+use crate::{Accumulable, Associative, Commutative, Deaccumulable, Timestamped};
+
+/// How a sliding window is computed from the panes it covers.
+pub trait PaneCombine<Lhs> {
+    /// Combines the panes of the next window, oldest first. Panes passed to a
+    /// previous call that have not been expired come first again.
+    fn combine<'a, P>(&mut self, panes: P) -> Lhs
+    where
+        P: ExactSizeIterator<Item = &'a Lhs>,
+        Lhs: 'a;
+
+    /// Called with the oldest pane once no further window covers it.
+    fn expire(&mut self, pane: &Lhs);
+}
+
+/// Folds every pane of a window anew.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Recombine;
+
+impl<Lhs> PaneCombine<Lhs> for Recombine
+where
+    Lhs: Associative + Clone,
+{
+    fn combine<'a, P>(&mut self, mut panes: P) -> Lhs
+    where
+        P: ExactSizeIterator<Item = &'a Lhs>,
+        Lhs: 'a,
+    {
+        let first = panes.next().expect("window without panes").clone();
+
+        panes.fold(first, |lhs, rhs| lhs.accumulate(rhs))
+    }
+
+    fn expire(&mut self, _pane: &Lhs) {}
+}
+
+/// Keeps a running total, adding panes as they enter the window and
+/// deaccumulating them as they leave it.
+#[derive(Clone, Debug)]
+pub struct Deaccumulate<Lhs> {
+    running: Option<Lhs>,
+    included: usize,
+}
+
+impl<Lhs> Default for Deaccumulate<Lhs> {
+    fn default() -> Self {
+        Self {
+            running: None,
+            included: 0,
+        }
+    }
+}
+
+impl<Lhs> PaneCombine<Lhs> for Deaccumulate<Lhs>
+where
+    Lhs: Commutative + Deaccumulable + Clone,
+{
+    fn combine<'a, P>(&mut self, panes: P) -> Lhs
+    where
+        P: ExactSizeIterator<Item = &'a Lhs>,
+        Lhs: 'a,
+    {
+        let len = panes.len();
+
+        for pane in panes.skip(self.included) {
+            match &mut self.running {
+                Some(running) => running.accumulate_from(pane),
+                None => self.running = Some(pane.clone()),
+            }
+        }
+
+        self.included = len;
+
+        self.running.clone().expect("window without panes")
+    }
+
+    fn expire(&mut self, pane: &Lhs) {
+        if self.included == 0 {
+            return;
+        }
+
+        self.included -= 1;
+
+        if self.included == 0 {
+            self.running = None;
+        } else if let Some(running) = &mut self.running {
+            running.deaccumulate_from(pane);
+        }
+    }
+}
+
+/// Pane bookkeeping shared by the `iter` and `stream` sliding windows.
+pub(crate) struct Panes<Rhs, Lhs, C>
+where
+    Rhs: Timestamped,
+{
+    size: SpanOf<Rhs>,
+    hop: SpanOf<Rhs>,
+    panes: VecDeque<(TimestampOf<Rhs>, Lhs)>,
+    next: Option<TimestampOf<Rhs>>,
+    ready: VecDeque<(TimestampOf<Rhs>, Lhs)>,
+    combine: C,
+}
+
+impl<Rhs, Lhs, C> Panes<Rhs, Lhs, C>
+where
+    Rhs: Timestamped,
+{
+    pub fn new(size: SpanOf<Rhs>, hop: SpanOf<Rhs>, combine: C) -> Self {
+        assert!(hop > Default::default(), "hop must be positive");
+        assert!(size >= hop, "window size must be at least hop");
+
+        Self {
+            size,
+            hop,
+            panes: VecDeque::new(),
+            next: None,
+            ready: VecDeque::new(),
+            combine,
+        }
+    }
+
+    pub fn with_combine<D>(self, combine: D) -> Panes<Rhs, Lhs, D> {
+        Panes {
+            size: self.size,
+            hop: self.hop,
+            panes: self.panes,
+            next: self.next,
+            ready: self.ready,
+            combine,
+        }
+    }
+
+    /// Whether no window is ready and no item is buffered in an open pane.
+    pub fn is_empty(&self) -> bool {
+        self.ready.is_empty() && self.panes.is_empty()
+    }
+
+    pub fn pop_ready(&mut self) -> Option<(TimestampOf<Rhs>, Lhs)> {
+        self.ready.pop_front()
+    }
+
+    /// Start of the earliest window covering the pane starting at `pane`.
+    fn first_window(&self, pane: TimestampOf<Rhs>) -> TimestampOf<Rhs> {
+        let mut start = pane;
+
+        loop {
+            let previous = start.sub_span(self.hop);
+
+            if previous < start && previous.add_span(self.size) > pane {
+                start = previous;
+            } else {
+                break start;
+            }
+        }
+    }
+}
+
+impl<Rhs, Lhs, C> Panes<Rhs, Lhs, C>
+where
+    Rhs: Timestamped,
+    Lhs: From<Rhs> + Accumulable<Rhs>,
+    C: PaneCombine<Lhs>,
+{
+    pub fn push(&mut self, rhs: Rhs) {
+        let pane = rhs.timestamp().floor(self.hop);
+
+        self.close_until(Some(pane));
+
+        match self.panes.back_mut() {
+            Some((last, lhs)) if *last >= pane => lhs.accumulate_from(&rhs),
+            _ => self.panes.push_back((pane, Lhs::from(rhs))),
+        }
+
+        if self.next.is_none() {
+            self.next = Some(self.first_window(pane));
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.close_until(None);
+    }
+
+    /// Closes every window ending at or before `limit`, or all of them.
+    fn close_until(&mut self, limit: Option<TimestampOf<Rhs>>) {
+        while let Some(start) = self.next {
+            let end = start.add_span(self.size);
+
+            if limit.is_some_and(|limit| end > limit) {
+                break;
+            }
+
+            let covered = self
+                .panes
+                .iter()
+                .take_while(|(pane, _)| *pane < end)
+                .count();
+
+            if covered > 0 {
+                let lhs = self
+                    .combine
+                    .combine(self.panes.iter().take(covered).map(|(_, lhs)| lhs));
+
+                self.ready.push_back((start, lhs));
+            }
+
+            let next = start.add_span(self.hop);
+
+            while let Some((_, lhs)) = self.panes.front().filter(|(pane, _)| *pane < next) {
+                self.combine.expire(lhs);
+                self.panes.pop_front();
+            }
+
+            self.next = match self.panes.front() {
+                Some((pane, _)) if *pane >= next.add_span(self.size) => {
+                    Some(self.first_window(*pane))
+                }
+                Some(_) => Some(next),
+                None => None,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Tick(u64);
+
+    impl Timestamped for Tick {
+        type Timestamp = u64;
+
+        fn timestamp(&self) -> u64 {
+            self.0
+        }
+    }
+
+    impl Accumulable for Tick {
+        fn accumulate_from(&mut self, rhs: &Self) {
+            self.0 = self.0.max(rhs.0);
+        }
+    }
+
+    impl Associative for Tick {}
+
+    #[test]
+    fn panes_is_empty() {
+        let mut panes = Panes::<Tick, Tick, _>::new(20, 10, Recombine);
+
+        assert!(panes.is_empty());
+
+        panes.push(Tick(3));
+
+        assert!(!panes.is_empty());
+
+        panes.flush();
+
+        assert!(!panes.is_empty());
+        assert_eq!(panes.pop_ready(), Some((0, Tick(3))));
+        assert!(panes.is_empty());
+    }
+}